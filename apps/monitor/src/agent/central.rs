@@ -0,0 +1,55 @@
+use std::env;
+
+use reqwest::{header, Client};
+
+use crate::{agent::monitor::AgentMonitor, monitors::pings::PingRecord};
+
+/// The main Miru instance an agent fetches monitors from and reports pings to.
+pub struct Central {
+    pub url: String,
+    token: String,
+    client: Client,
+}
+
+impl Central {
+    /// Read the instance URL and token from `MONITOR_URL` and `AGENT_TOKEN`.
+    pub fn from_env() -> Result<Self, String> {
+        let url = env::var("MONITOR_URL")
+            .map_err(|_| "Environment variable 'MONITOR_URL' not found".to_string())?;
+        let token = env::var("AGENT_TOKEN")
+            .map_err(|_| "Environment variable 'AGENT_TOKEN' not found".to_string())?;
+
+        Ok(Central {
+            url: url.trim_end_matches('/').to_string(),
+            token,
+            client: Client::new(),
+        })
+    }
+
+    /// Get the monitors the agent should check.
+    pub async fn monitors(&self) -> Result<Vec<AgentMonitor>, Box<dyn std::error::Error>> {
+        let res = self
+            .client
+            .get(format!("{}/agents/monitors", self.url))
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(serde_json::from_str(&res.text().await?)?)
+    }
+
+    /// Report a ping made by the agent.
+    pub async fn report(&self, record: &PingRecord) -> Result<(), Box<dyn std::error::Error>> {
+        self.client
+            .post(format!("{}/agents/pings", self.url))
+            .bearer_auth(&self.token)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(record)?)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+mod central;
+pub mod monitor;
+
+use std::{
+    collections::HashMap,
+    env,
+    future::Future,
+    pin::Pin,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+use central::Central;
+use log::{error, info, warn};
+use monitor::AgentMonitor;
+use tokio_cron_scheduler::{Job, JobScheduler};
+use uuid::Uuid;
+
+use crate::{
+    cron::schedule::Schedule,
+    monitors::{
+        create::{attempt, forget_running, limit_retries, running_flag, RunningGuard},
+        get::Monitor,
+    },
+    ping::PingOptions,
+    shutdown::{self, Task},
+};
+
+/// How often the agent fetches the monitors it should check
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// If the monitor runs as a probe agent, checking monitors for a main instance
+/// instead of scheduling them from the database.
+pub fn enabled() -> bool {
+    env::var("AGENT_TOKEN").is_ok()
+}
+
+/// A job checking a monitor, and the monitor as it was when the job was created.
+struct AgentJob {
+    id: Uuid,
+    monitor: AgentMonitor,
+}
+
+/// Run as a probe agent, checking the main instance's monitors and reporting the pings back.
+/// The monitors are fetched again every `SYNC_INTERVAL`, so changes are picked up without a restart.
+/// On SIGTERM or Ctrl-C, running checks get to finish and report their pings before it returns.
+pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let central = Arc::new(Central::from_env()?);
+
+    let sched = JobScheduler::new().await?;
+    sched.start().await?;
+
+    let mut jobs: HashMap<String, AgentJob> = HashMap::new();
+
+    info!("Reporting pings to {}", central.url);
+
+    let signalled = shutdown::signalled();
+    tokio::pin!(signalled);
+
+    loop {
+        match central.monitors().await {
+            Ok(monitors) => sync(&sched, &central, &mut jobs, monitors).await,
+            Err(e) => error!("Failed to fetch monitors from {}: {e}", central.url),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(SYNC_INTERVAL) => {}
+            _ = &mut signalled => break,
+        }
+    }
+
+    shutdown::drain(Some(sched)).await;
+
+    Ok(())
+}
+
+/// Add, replace and remove jobs so they match the monitors of the main instance.
+async fn sync(
+    sched: &JobScheduler,
+    central: &Arc<Central>,
+    jobs: &mut HashMap<String, AgentJob>,
+    monitors: Vec<AgentMonitor>,
+) {
+    let monitors = monitors
+        .into_iter()
+        .map(|monitor| (monitor.id.clone(), monitor))
+        .collect::<HashMap<_, _>>();
+
+    // Remove the jobs of deleted monitors, and of changed ones so they're created again
+    let stale = jobs
+        .iter()
+        .filter(|(id, job)| {
+            monitors
+                .get(*id)
+                .is_none_or(|monitor| *monitor != job.monitor)
+        })
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>();
+
+    for monitor_id in stale {
+        if let Some(job) = jobs.remove(&monitor_id) {
+            if let Err(e) = sched.remove(&job.id).await {
+                error!("Failed to remove job {}: {e}", job.id);
+            }
+        }
+
+        if !monitors.contains_key(&monitor_id) {
+            forget_running(&monitor_id);
+        }
+    }
+
+    for (monitor_id, monitor) in monitors {
+        if jobs.contains_key(&monitor_id) {
+            continue;
+        }
+
+        match add_job(sched, central, monitor.clone().into()).await {
+            Ok(id) => {
+                jobs.insert(monitor_id, AgentJob { id, monitor });
+            }
+            Err(e) => error!("Failed to create job for monitor {monitor_id}: {e}"),
+        }
+    }
+}
+
+/// Add a job checking a monitor on its schedule and reporting every ping.
+async fn add_job(
+    sched: &JobScheduler,
+    central: &Arc<Central>,
+    monitor: Monitor,
+) -> Result<Uuid, Box<dyn std::error::Error>> {
+    let schedule = Schedule::for_monitor(&monitor)?;
+
+    info!(
+        "Creating job for {} with type {} and schedule {schedule}",
+        monitor.url, monitor.r#type
+    );
+
+    let monitor = limit_retries(monitor, &schedule);
+    let monitor = Arc::new(Monitor {
+        options: PingOptions {
+            timeout: schedule.period(),
+            ..monitor.options
+        },
+        ..monitor
+    });
+    let central = Arc::clone(central);
+    let running = running_flag(&monitor.id);
+    let job_schedule = schedule.clone();
+
+    let run = move |_, _| {
+        let monitor = Arc::clone(&monitor);
+        let central = Arc::clone(&central);
+        let running = Arc::clone(&running);
+        let delay = job_schedule.delay();
+        Box::pin(async move {
+            // Wait out the stagger offset and jitter first, a run that's only waiting isn't running yet
+            tokio::time::sleep(delay).await;
+
+            // Checks starting while the agent shuts down are skipped, the ones already running are waited for
+            let _task = match Task::start() {
+                Some(task) => task,
+                None => return,
+            };
+
+            if running.swap(true, Ordering::AcqRel) {
+                warn!(
+                    "Skipping check for {}, the previous one is still running",
+                    monitor.url
+                );
+                return;
+            }
+
+            let _guard = RunningGuard(running);
+
+            for options in monitor.options.expand() {
+                let record = match attempt(&monitor, &options).await {
+                    Some((record, _)) => record,
+                    None => return,
+                };
+
+                if let Err(e) = central.report(&record).await {
+                    error!("Failed to report ping for {}: {e}", monitor.url);
+                }
+            }
+        }) as Pin<Box<dyn Future<Output = ()> + Send>>
+    };
+
+    let job = match &schedule {
+        Schedule::Cron(expr) => Job::new_async(expr.as_str(), run)?,
+        Schedule::Every { period, .. } => Job::new_repeated_async(*period, run)?,
+    };
+
+    Ok(sched.add(job).await?)
+}
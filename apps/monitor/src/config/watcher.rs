@@ -56,7 +56,7 @@ pub async fn watch<P: AsRef<Path>>(path: P) -> notify::Result<()> {
                         Ok(config) => config,
                         Err(_) => {
                             warn!("Failed to parse config file. Falling back to default config.");
-                            DEFAULT_CONFIG
+                            DEFAULT_CONFIG.clone()
                         }
                     };
 
@@ -98,12 +98,11 @@ pub fn start_watcher() -> Result<(), String> {
     #[cfg(not(debug_assertions))]
     let config_path = path.join("./config.toml");
 
+    // Awaited rather than blocked on, a blocked worker would keep the runtime from shutting down
     let handle = tokio::spawn(async move {
-        futures::executor::block_on(async {
-            if let Err(e) = watch(config_path).await {
-                println!("error: {e:?}")
-            }
-        });
+        if let Err(e) = watch(config_path).await {
+            println!("error: {e:?}")
+        }
     });
 
     HANDLE
@@ -0,0 +1,388 @@
+use std::{
+    collections::HashSet,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgListener, Connection, PgConnection};
+
+use crate::{
+    config::get_config,
+    cron::worker::{load_jobs, unload_jobs},
+    events,
+    monitors::{self, get::get_monitor, health::load_registry, locations::evaluate},
+    shutdown::Task,
+    EVENT_REGISTRY, MON_REGISTRY, POOL, SCHED,
+};
+
+/// The advisory lock key of shard 0, the other shards use the keys right after it
+const LOCK_KEY: i64 = 0x6d69_7275_0000;
+
+/// The channel job changes are forwarded on, to reach the instance leading a shard
+const CHANNEL: &str = "miru_cluster";
+
+/// How often the lock connection is checked. Once it's gone Postgres has released the shards,
+/// and until the leader notices, it runs their jobs alongside whoever takes them over
+const LIVENESS: Duration = Duration::from_secs(1);
+
+/// The longest wait between attempts to listen for forwarded job changes
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The shards this instance leads
+static SHARDS: Lazy<RwLock<HashSet<u32>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+/// A job change forwarded to the instance leading the monitor or event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", content = "id", rename_all = "snake_case")]
+pub enum Forward {
+    CreateMonitor(String),
+    UpdateMonitor(String),
+    PauseMonitor(String),
+    ResumeMonitor(String),
+    RemoveMonitor(String),
+    CreateEvent(String),
+    UpdateEvent(String),
+    RemoveEvent(String),
+    /// Check a monitor's health after a probe agent reported a ping for it
+    CheckHealth(String),
+}
+
+impl Forward {
+    /// Send the change to every instance, the one leading its shard runs it.
+    pub async fn send(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let pool = POOL.clone();
+        let payload = serde_json::to_string(self)?;
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(CHANNEL)
+            .bind(payload)
+            .execute(&pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        let (sched, mon_reg, event_reg) =
+            match (SCHED.get(), MON_REGISTRY.get(), EVENT_REGISTRY.get()) {
+                (Some(sched), Some(mon_reg), Some(event_reg)) => (sched, mon_reg, event_reg),
+                _ => return Err("Failed to get scheduler or registries".into()),
+            };
+
+        match self {
+            Forward::CreateMonitor(id) => {
+                let monitor = get_monitor(&id).await?;
+                if !monitor.paused {
+                    monitors::create_job(monitor, sched.lock().await, mon_reg.lock().await).await?;
+                }
+            }
+            Forward::UpdateMonitor(id) => {
+                monitors::update_job(id, sched.lock().await, mon_reg.lock().await).await?
+            }
+            Forward::PauseMonitor(id) => {
+                monitors::pause_job(id, sched.lock().await, mon_reg.lock().await).await?
+            }
+            Forward::ResumeMonitor(id) => {
+                monitors::resume_job(id, sched.lock().await, mon_reg.lock().await).await?
+            }
+            Forward::RemoveMonitor(id) => {
+                monitors::remove_job(&id, sched.lock().await, mon_reg.lock().await).await?;
+            }
+            Forward::CreateEvent(id) => {
+                events::create_job(id, sched.lock().await, event_reg.lock().await).await?;
+            }
+            Forward::UpdateEvent(id) => {
+                events::update_job(id, sched.lock().await, event_reg.lock().await).await?
+            }
+            Forward::RemoveEvent(id) => {
+                events::remove_job(&id, sched.lock().await, event_reg.lock().await).await?;
+            }
+            Forward::CheckHealth(id) => evaluate(&id).await,
+        }
+
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        match self {
+            Forward::CreateMonitor(id)
+            | Forward::UpdateMonitor(id)
+            | Forward::PauseMonitor(id)
+            | Forward::ResumeMonitor(id)
+            | Forward::RemoveMonitor(id)
+            | Forward::CreateEvent(id)
+            | Forward::UpdateEvent(id)
+            | Forward::RemoveEvent(id)
+            | Forward::CheckHealth(id) => id,
+        }
+    }
+}
+
+/// The shard a monitor or event belongs to, based on its ID.
+pub fn shard_of(id: &str) -> u32 {
+    shard_in(id, get_config().cluster.shards)
+}
+
+/// The shard an ID falls in out of `shards`.
+fn shard_in(id: &str, shards: u32) -> u32 {
+    let hash = openssl::sha::sha256(id.as_bytes());
+    (u64::from_be_bytes(hash[..8].try_into().unwrap_or_default()) % shards.max(1) as u64) as u32
+}
+
+/// If this instance should run the jobs of a monitor or event.
+/// Always true when clustering is disabled.
+pub fn owns(id: &str) -> bool {
+    if !get_config().cluster.enabled {
+        return true;
+    }
+
+    SHARDS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&shard_of(id))
+}
+
+/// Start leader election and listening for forwarded job changes.
+/// Returns false if clustering is disabled, in which case the caller loads the jobs itself.
+pub fn start() -> bool {
+    let config = get_config().cluster;
+
+    if !config.enabled {
+        return false;
+    }
+
+    info!(
+        "Clustering enabled with {} shard(s), waiting to lead one",
+        config.shards.max(1)
+    );
+
+    tokio::spawn(elect());
+    tokio::spawn(listen());
+
+    true
+}
+
+/// Try to lead free shards every heartbeat, and give them all up if the lock connection is lost.
+/// Advisory locks belong to the connection that took them, so when a leader goes away
+/// Postgres releases its shards and a standby takes them over on its next heartbeat.
+///
+/// The connection is checked every [`LIVENESS`] rather than every heartbeat, since a leader that lost it
+/// keeps running its shards until it notices. Checks can still run twice for up to about that long.
+async fn elect() {
+    let mut conn: Option<PgConnection> = None;
+    // Shards nobody led on the last heartbeat
+    let mut free = HashSet::<u32>::new();
+    let mut last_heartbeat: Option<Instant> = None;
+
+    loop {
+        let config = get_config().cluster;
+        tokio::time::sleep(LIVENESS).await;
+
+        let connection = match conn.as_mut() {
+            Some(connection) => connection,
+            None => match PgConnection::connect_with(&POOL.connect_options()).await {
+                Ok(connection) => conn.insert(connection),
+                Err(e) => {
+                    error!("Failed to connect for leader election: {e}");
+                    continue;
+                }
+            },
+        };
+
+        // A connection that doesn't answer in time is as good as lost, its locks may already be gone
+        let alive =
+            match tokio::time::timeout(LIVENESS, sqlx::query("SELECT 1").execute(&mut *connection))
+                .await
+            {
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("timed out".to_string()),
+            };
+
+        if let Err(e) = alive {
+            error!("Lost the leader election connection: {e}");
+            conn = None;
+            release().await;
+            continue;
+        }
+
+        let heartbeat = Duration::from_secs(config.heartbeat.max(1));
+        if last_heartbeat.is_some_and(|last| last.elapsed() < heartbeat) {
+            continue;
+        }
+        last_heartbeat = Some(Instant::now());
+
+        let owned = SHARDS.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut gained = vec![];
+        let mut still_free = HashSet::new();
+
+        for shard in (0..config.shards.max(1)).filter(|shard| !owned.contains(shard)) {
+            let key = LOCK_KEY + shard as i64;
+
+            let locked = sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_lock($1)")
+                .bind(key)
+                .fetch_one(&mut *connection)
+                .await;
+
+            match locked {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    error!("Failed to lock shard {shard}: {e}");
+                    break;
+                }
+            }
+
+            // Take one shard straight away, and others only once they've been free for a
+            // whole heartbeat, so instances starting together spread the shards between them
+            if (owned.is_empty() && gained.is_empty()) || free.contains(&shard) {
+                gained.push(shard);
+                continue;
+            }
+
+            still_free.insert(shard);
+
+            if let Err(e) = sqlx::query("SELECT pg_advisory_unlock($1)")
+                .bind(key)
+                .execute(&mut *connection)
+                .await
+            {
+                error!("Failed to unlock shard {shard}: {e}");
+            }
+        }
+
+        free = still_free;
+
+        if gained.is_empty() {
+            continue;
+        }
+
+        SHARDS
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(gained.iter().copied());
+
+        info!("Leading shard(s) {gained:?}, loading their jobs");
+
+        load_registry().await;
+        load_jobs().await;
+    }
+}
+
+/// Give up every shard and remove their jobs.
+async fn release() {
+    let released = std::mem::take(&mut *SHARDS.write().unwrap_or_else(|e| e.into_inner()));
+
+    if released.is_empty() {
+        return;
+    }
+
+    warn!("No longer leading shard(s) {released:?}, removing their jobs");
+    unload_jobs().await;
+}
+
+/// Start listening for forwarded job changes, retrying with a growing delay until it works.
+/// Changes forwarded in the meantime are lost, the reconciler catches up with them.
+async fn subscribe() -> PgListener {
+    let mut backoff = Duration::from_secs(1);
+
+    loop {
+        let listener = match PgListener::connect_with(&POOL.clone()).await {
+            Ok(mut listener) => match listener.listen(CHANNEL).await {
+                Ok(()) => Ok(listener),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+
+        match listener {
+            Ok(listener) => return listener,
+            Err(e) => error!(
+                "Failed to listen for forwarded jobs, retrying in {}s: {e}",
+                backoff.as_secs()
+            ),
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Run job changes forwarded by other instances, for the shards this instance leads.
+async fn listen() {
+    let mut listener = subscribe().await;
+
+    loop {
+        // The listener reconnects by itself on the next call after an error
+        let notification = match listener.recv().await {
+            Ok(notification) => notification,
+            Err(e) => {
+                error!("Failed to receive forwarded job: {e}");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        let forward = match serde_json::from_str::<Forward>(notification.payload()) {
+            Ok(forward) => forward,
+            Err(e) => {
+                error!("Invalid forwarded job {:?}: {e}", notification.payload());
+                continue;
+            }
+        };
+
+        if !owns(forward.id()) {
+            continue;
+        }
+
+        // Jobs are reloaded from the database on the next start, a change missed now isn't lost
+        let task = match Task::start() {
+            Some(task) => task,
+            None => {
+                warn!("Shutting down, not running forwarded job change {forward:?}");
+                continue;
+            }
+        };
+
+        info!("Running forwarded job change {forward:?}");
+
+        tokio::spawn(async move {
+            let _task = task;
+            if let Err(e) = forward.clone().run().await {
+                error!("Failed to run forwarded job change {forward:?}: {e}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shards_are_stable() {
+        assert_eq!(shard_in("mon_a", 8), shard_in("mon_a", 8));
+        assert!(shard_in("mon_a", 8) < 8);
+    }
+
+    #[test]
+    fn a_single_shard_owns_everything() {
+        for id in ["mon_a", "mon_b", "evt_c"] {
+            assert_eq!(shard_in(id, 1), 0);
+            assert_eq!(shard_in(id, 0), 0);
+        }
+    }
+
+    #[test]
+    fn ids_spread_across_shards() {
+        let mut counts = [0; 4];
+        for i in 0..400 {
+            counts[shard_in(&format!("mon_{i}"), 4) as usize] += 1;
+        }
+
+        assert!(counts.iter().all(|count| *count > 50), "{counts:?}");
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::get_config;
+
+/// A semaphore and the limit it currently enforces, so it can be resized when the config changes.
+type Limiter = (usize, Arc<Semaphore>);
+
+static GLOBAL: Lazy<Mutex<Option<Limiter>>> = Lazy::new(|| Mutex::new(None));
+static HOSTS: Lazy<Mutex<HashMap<String, Limiter>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A slot to run a check in, it's given back when dropped.
+pub struct Slot {
+    host: Option<(String, OwnedSemaphorePermit)>,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if let Some((key, permit)) = self.host.take() {
+            drop(permit);
+
+            // Forget hosts nothing is checking or waiting for, so the map doesn't keep every host ever checked.
+            // Only the map holding the semaphore means no permits are out and nobody is queued
+            let mut hosts = HOSTS.lock().unwrap_or_else(|e| e.into_inner());
+            if hosts
+                .get(&key)
+                .is_some_and(|(_, semaphore)| Arc::strong_count(semaphore) == 1)
+            {
+                hosts.remove(&key);
+            }
+        }
+    }
+}
+
+/// Get the semaphore for a limit, resizing it if the limit changed.
+/// Turning a limit off drops its semaphore, so checks started before it's turned on again don't count.
+fn limiter(current: &mut Option<Limiter>, limit: usize) -> Option<Arc<Semaphore>> {
+    if limit == 0 {
+        *current = None;
+        return None;
+    }
+
+    match current {
+        Some((l, semaphore)) => {
+            if *l != limit {
+                resize(semaphore, *l, limit);
+                *l = limit;
+            }
+            Some(semaphore.clone())
+        }
+        None => {
+            let semaphore = Arc::new(Semaphore::new(limit));
+            *current = Some((limit, semaphore.clone()));
+            Some(semaphore)
+        }
+    }
+}
+
+/// Change the number of permits of a semaphore in place, so checks already holding one count towards the new limit.
+/// When it shrinks below the permits that are out, the rest are taken back as the checks finish.
+fn resize(semaphore: &Arc<Semaphore>, from: usize, to: usize) {
+    if to >= from {
+        semaphore.add_permits(to - from);
+        return;
+    }
+
+    let excess = from - to;
+    let missing = excess - semaphore.forget_permits(excess);
+
+    if missing > 0 {
+        let semaphore = semaphore.clone();
+        tokio::spawn(async move {
+            if let Ok(permits) = semaphore
+                .acquire_many_owned(missing.try_into().unwrap_or(u32::MAX))
+                .await
+            {
+                permits.forget();
+            }
+        });
+    }
+}
+
+/// Get the host a monitor URL points to, without the scheme, port or path.
+fn host(url: &str) -> String {
+    let authority = url
+        .trim_start_matches("http://")
+        .trim_start_matches("https://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+
+    host.to_lowercase()
+}
+
+/// Wait for a slot to check `url` in, limited by `scheduler.max_per_host` and `scheduler.max_concurrent`.
+/// Waiting checks are queued in order. Returns the slot and how long it took to get it.
+pub async fn acquire(url: &str) -> (Slot, Duration) {
+    let config = get_config().scheduler;
+    let start = Instant::now();
+
+    let key = host(url);
+
+    let host = {
+        let mut hosts = HOSTS.lock().unwrap_or_else(|e| e.into_inner());
+        let mut current = hosts.remove(&key);
+        let semaphore = limiter(&mut current, config.max_per_host);
+        if let Some(current) = current {
+            hosts.insert(key.clone(), current);
+        }
+        semaphore
+    };
+
+    let global = {
+        let mut global = GLOBAL.lock().unwrap_or_else(|e| e.into_inner());
+        limiter(&mut global, config.max_concurrent)
+    };
+
+    // Wait for the host first, so checks queued behind a busy host don't hold up global slots
+    let host = match host {
+        Some(semaphore) => semaphore
+            .acquire_owned()
+            .await
+            .ok()
+            .map(|permit| (key, permit)),
+        None => None,
+    };
+
+    let global = match global {
+        Some(semaphore) => semaphore.acquire_owned().await.ok(),
+        None => None,
+    };
+
+    (
+        Slot {
+            host,
+            _global: global,
+        },
+        start.elapsed(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_host() {
+        assert_eq!(host("https://Example.com:8443/status"), "example.com");
+        assert_eq!(host("http://[2001:db8::1]:80/"), "2001:db8::1");
+        assert_eq!(host("db.internal:5432"), "db.internal");
+    }
+
+    #[tokio::test]
+    async fn resizes_in_place() {
+        let mut current = None;
+        let semaphore = limiter(&mut current, 2).unwrap();
+        let first = semaphore.clone().acquire_owned().await.unwrap();
+
+        // Growing adds permits next to the one that's out
+        let grown = limiter(&mut current, 4).unwrap();
+        assert!(Arc::ptr_eq(&semaphore, &grown));
+        assert_eq!(semaphore.available_permits(), 3);
+
+        // Shrinking below what's out takes permits back as they're released
+        let second = semaphore.clone().acquire_owned().await.unwrap();
+        limiter(&mut current, 1).unwrap();
+        assert_eq!(semaphore.available_permits(), 0);
+
+        drop(first);
+        tokio::task::yield_now().await;
+        assert_eq!(semaphore.available_permits(), 0);
+
+        drop(second);
+        assert_eq!(semaphore.available_permits(), 1);
+
+        assert!(limiter(&mut current, 0).is_none());
+        assert!(current.is_none());
+    }
+
+    #[tokio::test]
+    async fn forgets_idle_hosts() {
+        let (slot, _) = acquire("https://idle.test/").await;
+        assert!(HOSTS.lock().unwrap().contains_key("idle.test"));
+
+        drop(slot);
+        assert!(!HOSTS.lock().unwrap().contains_key("idle.test"));
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use log::{error, info, warn};
+use sqlx::query;
+
+use crate::{
+    config::get_config,
+    cron::{cluster::owns, schedule::Schedule, stats, worker::MonitorJobMetadata},
+    events::{self, status::EventStatus},
+    monitors::{
+        self,
+        create::add_job,
+        get::{get_monitor, get_monitors, Monitor},
+    },
+    shutdown, EVENT_REGISTRY, MON_REGISTRY, POOL, SCHED,
+};
+
+/// Start comparing the jobs with the database every `scheduler.reconcile_interval`.
+/// The interval is read again after every run, so it can be changed without a restart.
+pub fn start() {
+    tokio::spawn(async {
+        loop {
+            let interval = get_config().scheduler.reconcile_interval;
+
+            // Check again later in case it gets enabled
+            if interval == 0 {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+
+            if shutdown::stopping() {
+                return;
+            }
+
+            reconcile_monitors().await;
+            reconcile_events().await;
+            prune_runs().await;
+        }
+    });
+}
+
+/// The schedule a monitor's job should have, the faster one if it's failing.
+fn expected_schedule(monitor: &Monitor, failing: bool) -> Result<Schedule, String> {
+    let interval = get_config().incidents.auto.failing_interval;
+
+    match failing {
+        true => match Schedule::for_failing_monitor(monitor, interval) {
+            Some(schedule) => Ok(schedule),
+            None => Schedule::for_monitor(monitor),
+        },
+        false => Schedule::for_monitor(monitor),
+    }
+}
+
+/// The monitor as it is now, if this instance should have a job for it.
+/// The list a pass started from can be outdated by the time a job is created or removed,
+/// like when a monitor was paused or deleted in between.
+async fn current(monitor_id: &str) -> Result<Option<Monitor>, sqlx::Error> {
+    match get_monitor(monitor_id).await {
+        Ok(monitor) if !monitor.paused && owns(monitor_id) => Ok(Some(monitor)),
+        Ok(_) | Err(sqlx::Error::RowNotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Create missing monitor jobs, remove orphaned and duplicate ones,
+/// and reschedule jobs that don't match their monitor anymore, like after a lost update.
+/// Every change is checked again against the database while holding the scheduler and registry,
+/// so a pause, create or delete that lands during the pass isn't undone.
+async fn reconcile_monitors() {
+    let (sched, reg) = match (SCHED.get(), MON_REGISTRY.get()) {
+        (Some(sched), Some(reg)) => (sched, reg),
+        _ => {
+            error!("Failed to get scheduler or monitor registry");
+            return;
+        }
+    };
+
+    let monitors = match get_monitors().await {
+        Ok(monitors) => monitors
+            .into_iter()
+            .filter(|monitor| !monitor.paused && owns(&monitor.id))
+            .map(|monitor| (monitor.id.clone(), monitor))
+            .collect::<HashMap<_, _>>(),
+        Err(e) => {
+            error!("Failed to fetch monitors: {e:?}");
+            return;
+        }
+    };
+
+    let jobs = reg.lock().await.clone();
+
+    // Jobs of monitors that were deleted, paused or are led by another instance
+    for jmd in jobs
+        .iter()
+        .filter(|jmd| !monitors.contains_key(&jmd.monitor_id))
+    {
+        let sched = sched.lock().await;
+        let reg = reg.lock().await;
+
+        match current(&jmd.monitor_id).await {
+            Ok(None) => {}
+            // Created or resumed since the list was fetched
+            Ok(Some(_)) => continue,
+            Err(e) => {
+                error!("Failed to fetch monitor {}: {e:?}", jmd.monitor_id);
+                continue;
+            }
+        }
+
+        if !reg.iter().any(|job| job.id == jmd.id) {
+            continue;
+        }
+
+        warn!(
+            "Removing orphaned job {} of monitor {}",
+            jmd.id, jmd.monitor_id
+        );
+
+        if let Err(e) = monitors::remove_job(&jmd.monitor_id, sched, reg).await {
+            error!("Failed to remove job {}: {e}", jmd.id);
+        }
+    }
+
+    for (monitor_id, monitor) in monitors {
+        let monitor_jobs = jobs
+            .iter()
+            .filter(|jmd| jmd.monitor_id == monitor_id)
+            .collect::<Vec<_>>();
+
+        let jmd = match monitor_jobs.split_first() {
+            Some((jmd, duplicates)) => {
+                for duplicate in duplicates {
+                    warn!(
+                        "Removing duplicate job {} of monitor {monitor_id}",
+                        duplicate.id
+                    );
+
+                    let sched = sched.lock().await;
+                    let mut reg = reg.lock().await;
+
+                    if let Err(e) = sched.remove(&duplicate.id).await {
+                        error!("Failed to remove job {}: {e}", duplicate.id);
+                        continue;
+                    }
+
+                    reg.retain(|job| job.id != duplicate.id);
+                }
+
+                jmd
+            }
+            None => {
+                let sched = sched.lock().await;
+                let reg = reg.lock().await;
+
+                // Created by a route since the registry was copied
+                if reg.iter().any(|jmd| jmd.monitor_id == monitor_id) {
+                    continue;
+                }
+
+                let monitor = match current(&monitor_id).await {
+                    Ok(Some(monitor)) => monitor,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        error!("Failed to fetch monitor {monitor_id}: {e:?}");
+                        continue;
+                    }
+                };
+
+                warn!("Monitor {monitor_id} has no job, creating it");
+
+                if let Err(e) = monitors::create_job(monitor, sched, reg).await {
+                    error!("Failed to create job for monitor {monitor_id}: {e}");
+                }
+
+                continue;
+            }
+        };
+
+        let expected = match expected_schedule(&monitor, jmd.failing) {
+            Ok(schedule) => schedule.to_string(),
+            Err(e) => {
+                error!("Monitor {monitor_id} has an invalid schedule: {e}");
+                continue;
+            }
+        };
+
+        // Jobs check their own copy of the monitor, so any changed setting needs a new job
+        if jmd.cron_expr == expected && jmd.fingerprint == monitor.fingerprint() {
+            continue;
+        }
+
+        reschedule(jmd).await;
+    }
+}
+
+/// Replace a monitor's job with one checking its current settings, keeping the faster schedule
+/// of a failing monitor so it isn't set back to its normal schedule until it recovers.
+async fn reschedule(jmd: &MonitorJobMetadata) {
+    let (sched, reg) = match (SCHED.get(), MON_REGISTRY.get()) {
+        (Some(sched), Some(reg)) => (sched, reg),
+        _ => return,
+    };
+
+    let monitor_id = &jmd.monitor_id;
+    let sched = sched.lock().await;
+    let mut registry = reg.lock().await;
+
+    // Replaced or removed since the registry was copied
+    if !registry.iter().any(|job| job.id == jmd.id) {
+        return;
+    }
+
+    let monitor = match current(monitor_id).await {
+        Ok(Some(monitor)) => monitor,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to fetch monitor {monitor_id}: {e:?}");
+            return;
+        }
+    };
+
+    let schedule = match expected_schedule(&monitor, jmd.failing) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            error!("Monitor {monitor_id} has an invalid schedule: {e}");
+            return;
+        }
+    };
+
+    match jmd.cron_expr == schedule.to_string() {
+        true => warn!("Job of monitor {monitor_id} checks outdated settings, rescheduling it"),
+        false => warn!(
+            "Job of monitor {monitor_id} runs on {} instead of {schedule}, rescheduling it",
+            jmd.cron_expr
+        ),
+    }
+
+    // The new job is added first, so the monitor is never left without one
+    if let Err(e) = add_job(monitor, schedule, jmd.failing, &sched, &mut registry).await {
+        error!("Failed to reschedule monitor {monitor_id}: {e}");
+        return;
+    }
+
+    if let Err(e) = sched.remove(&jmd.id).await {
+        error!("Failed to remove job {}: {e}", jmd.id);
+    }
+
+    registry.retain(|job| job.id != jmd.id);
+}
+
+/// Create missing event jobs and remove the ones of deleted, completed or cancelled events.
+async fn reconcile_events() {
+    let (sched, reg) = match (SCHED.get(), EVENT_REGISTRY.get()) {
+        (Some(sched), Some(reg)) => (sched, reg),
+        _ => {
+            error!("Failed to get scheduler or event registry");
+            return;
+        }
+    };
+
+    let pool = POOL.clone();
+
+    let events = match query!(
+        "SELECT id, status, auto_complete, recurrence FROM events WHERE status IN ('scheduled', 'in_progress')"
+    )
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(events) => events
+            .into_iter()
+            .filter(|event| owns(&event.id))
+            .map(|event| {
+                let ends = event.auto_complete || event.recurrence.is_some();
+                let pending = EventStatus::from(event.status).is_pending(ends);
+                (event.id, pending)
+            })
+            .collect::<HashMap<_, _>>(),
+        Err(e) => {
+            error!("Failed to fetch events: {e:?}");
+            return;
+        }
+    };
+
+    let jobs = reg.lock().await.clone();
+
+    for jmd in jobs
+        .iter()
+        .filter(|jmd| !events.contains_key(&jmd.event_id))
+    {
+        warn!("Removing orphaned job {} of event {}", jmd.id, jmd.event_id);
+
+        let sched = sched.lock().await;
+        let mut reg = reg.lock().await;
+
+        // Jobs of completed events have usually fired already, drop them from the registry either way
+        if let Err(e) = sched.remove(&jmd.id).await {
+            error!("Failed to remove job {}: {e}", jmd.id);
+        }
+
+        reg.retain(|job| job.id != jmd.id);
+    }
+
+    // Events in progress that don't complete automatically have nothing left to schedule
+    for (event_id, _) in events.into_iter().filter(|(_, pending)| *pending) {
+        if jobs.iter().any(|jmd| jmd.event_id == event_id) {
+            continue;
+        }
+
+        warn!("Event {event_id} has no job, creating it");
+
+        match events::create_job(event_id.clone(), sched.lock().await, reg.lock().await).await {
+            Ok(_) => info!("Created job for event {event_id}"),
+            Err(e) => error!("Failed to create job for event {event_id}: {e}"),
+        }
+    }
+}
+
+/// Forget the last runs of jobs that were removed since.
+async fn prune_runs() {
+    let (mon_reg, event_reg) = match (MON_REGISTRY.get(), EVENT_REGISTRY.get()) {
+        (Some(mon_reg), Some(event_reg)) => (mon_reg, event_reg),
+        _ => return,
+    };
+
+    let mut jobs = mon_reg
+        .lock()
+        .await
+        .iter()
+        .map(|jmd| jmd.id)
+        .collect::<HashSet<_>>();
+    jobs.extend(event_reg.lock().await.iter().map(|jmd| jmd.id));
+
+    stats::prune(&jobs);
+}
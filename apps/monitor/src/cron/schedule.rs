@@ -0,0 +1,421 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use chrono::Utc;
+use croner::Cron;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::get_config, monitors::get::Monitor};
+
+/// The shortest interval a monitor can be checked on
+pub static MIN_INTERVAL_SECONDS: u64 = 10;
+/// How many upcoming fires of a cron expression are looked at to find its period
+static PERIOD_SAMPLES: usize = 16;
+
+/// The unit of a monitor's `interval` column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntervalUnit {
+    Seconds,
+    #[default]
+    Minutes,
+}
+
+impl FromStr for IntervalUnit {
+    type Err = String;
+
+    fn from_str(unit: &str) -> Result<Self, Self::Err> {
+        match unit {
+            "seconds" => Ok(IntervalUnit::Seconds),
+            "minutes" => Ok(IntervalUnit::Minutes),
+            other => Err(format!(
+                "Invalid interval unit {other:?}, it must be seconds or minutes"
+            )),
+        }
+    }
+}
+
+/// When a monitor's job fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// A croner pattern with seconds, fires aligned to the clock
+    Cron(String),
+    /// Fires every `period` from when the job was created, for intervals cron can't express.
+    /// Each run waits `offset` first so these monitors are staggered as well.
+    Every { period: Duration, offset: Duration },
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Cron(expr) => write!(f, "{expr}"),
+            Schedule::Every { period, offset } => {
+                write!(f, "every {}s (+{}s)", period.as_secs(), offset.as_secs())
+            }
+        }
+    }
+}
+
+impl Schedule {
+    /// Work out the schedule of a monitor. A raw cron expression takes priority over the interval.
+    /// Invalid values are returned as an error instead of falling back to a default.
+    pub fn for_monitor(monitor: &Monitor) -> Result<Schedule, String> {
+        let schedule = match monitor.cron.as_deref().map(str::trim) {
+            Some(expr) if !expr.is_empty() => Schedule::from_cron(expr)?,
+            _ => Schedule::from_interval(
+                monitor.interval,
+                monitor.interval_unit,
+                monitor_offset(monitor),
+            )?,
+        };
+
+        Ok(schedule)
+    }
+
+    /// The faster schedule a monitor is checked on while it's failing, or `None` if it's
+    /// already checked at least as often.
+    pub fn for_failing_monitor(monitor: &Monitor, seconds: u64) -> Option<Schedule> {
+        let normal = match monitor.interval_unit {
+            IntervalUnit::Seconds => monitor.interval as u64,
+            IntervalUnit::Minutes => monitor.interval as u64 * 60,
+        };
+
+        // Cron expressions can fire at any rate, so they're always sped up
+        let has_cron = monitor
+            .cron
+            .as_deref()
+            .is_some_and(|expr| !expr.trim().is_empty());
+
+        if !has_cron && normal <= seconds {
+            return None;
+        }
+
+        Schedule::from_interval(
+            seconds.try_into().unwrap_or(i32::MAX),
+            IntervalUnit::Seconds,
+            monitor_offset(monitor),
+        )
+        .ok()
+    }
+
+    /// Validate a croner expression. Standard 5 field expressions are accepted and fire at second 0.
+    // https://github.com/hexagon/croner-rust?tab=readme-ov-file#pattern
+    pub fn from_cron(expr: &str) -> Result<Schedule, String> {
+        let expr = match expr.split_whitespace().count() {
+            5 => format!("0 {expr}"),
+            _ => expr.to_string(),
+        };
+
+        // Parsed with the same options the scheduler uses when the job is created
+        Cron::new(&expr)
+            .with_seconds_required()
+            .with_dom_and_dow()
+            .parse()
+            .map_err(|e| format!("Invalid cron expression {expr:?}: {e}"))?;
+
+        Ok(Schedule::Cron(expr))
+    }
+
+    /// Turn an interval into a schedule, shifted by `offset` seconds (wrapped to the interval).
+    /// Intervals that evenly divide a minute, an hour or a day are aligned to the clock with a cron pattern,
+    /// anything else repeats from when the job is created.
+    pub fn from_interval(
+        interval: i32,
+        unit: IntervalUnit,
+        offset: u64,
+    ) -> Result<Schedule, String> {
+        if interval <= 0 {
+            return Err(format!("Invalid interval {interval}, it must be positive"));
+        }
+
+        let seconds = match unit {
+            IntervalUnit::Seconds => interval as u64,
+            IntervalUnit::Minutes => interval as u64 * 60,
+        };
+
+        if seconds < MIN_INTERVAL_SECONDS {
+            return Err(format!(
+                "Invalid interval {seconds}s, it must be at least {MIN_INTERVAL_SECONDS}s"
+            ));
+        }
+
+        let offset = offset % seconds;
+        let (sec, min, hour) = (offset % 60, offset / 60 % 60, offset / 3600);
+
+        let expr = match seconds {
+            s if s < 60 && 60 % s == 0 => Some(format!("{} * * * * *", field(offset, s, 60))),
+            s if s < 3600 && s % 60 == 0 && 60 % (s / 60) == 0 => {
+                Some(format!("{sec} {} * * * *", field(offset / 60, s / 60, 60)))
+            }
+            s if s <= 86400 && s % 3600 == 0 && 24 % (s / 3600) == 0 => {
+                Some(format!("{sec} {min} {} * * *", field(hour, s / 3600, 24)))
+            }
+            _ => None,
+        };
+
+        Ok(match expr {
+            Some(expr) => Schedule::Cron(expr),
+            None => Schedule::Every {
+                period: Duration::from_secs(seconds),
+                offset: Duration::from_secs(offset),
+            },
+        })
+    }
+
+    /// The shortest time between two runs. Cron expressions can fire irregularly,
+    /// so it's the shortest gap between their next few fires.
+    pub fn period(&self) -> Option<Duration> {
+        match self {
+            Schedule::Every { period, .. } => Some(*period),
+            Schedule::Cron(expr) => {
+                let cron = Cron::new(expr)
+                    .with_seconds_required()
+                    .with_dom_and_dow()
+                    .parse()
+                    .ok()?;
+
+                let fires = cron
+                    .iter_after(Utc::now())
+                    .take(PERIOD_SAMPLES)
+                    .collect::<Vec<_>>();
+
+                fires
+                    .windows(2)
+                    .filter_map(|fires| (fires[1] - fires[0]).to_std().ok())
+                    .min()
+            }
+        }
+    }
+
+    /// The most jitter a run gets, at most half the period so a monitor checked more often
+    /// than the configured jitter still gets most of its checks.
+    fn max_jitter(&self, jitter: Duration) -> Duration {
+        match self.period() {
+            Some(period) => jitter.min(period / 2),
+            None => jitter,
+        }
+    }
+
+    /// How long a run should wait before checking, the stagger offset of repeated schedules
+    /// plus the random jitter from the config.
+    pub fn delay(&self) -> Duration {
+        let max = self.max_jitter(Duration::from_secs(get_config().scheduler.jitter));
+
+        let jitter = match max.as_millis() as u64 {
+            0 => 0,
+            max => rand::rng().random_range(0..=max),
+        };
+
+        self.delay_with(Duration::from_millis(jitter))
+    }
+
+    /// The delay of a run with the given jitter, kept below the period so a run
+    /// is done waiting before the next one fires.
+    fn delay_with(&self, jitter: Duration) -> Duration {
+        let offset = match self {
+            Schedule::Cron(_) => Duration::ZERO,
+            Schedule::Every { offset, .. } => *offset,
+        };
+
+        match self.period() {
+            Some(period) if offset + jitter >= period => {
+                period.saturating_sub(Duration::from_secs(1))
+            }
+            _ => offset + jitter,
+        }
+    }
+}
+
+/// The stagger offset of a monitor, if staggering is enabled.
+fn monitor_offset(monitor: &Monitor) -> u64 {
+    match get_config().scheduler.stagger {
+        true => stagger_offset(&monitor.id),
+        false => 0,
+    }
+}
+
+/// A stable offset for a monitor, so monitors on the same interval are spread across it
+/// and keep their slot between restarts.
+fn stagger_offset(monitor_id: &str) -> u64 {
+    let hash = openssl::sha::sha256(monitor_id.as_bytes());
+    u64::from_be_bytes(hash[..8].try_into().unwrap_or_default())
+}
+
+/// Build a cron field that fires every `step` units starting at `start`, for a field counting up to `max`.
+fn field(start: u64, step: u64, max: u64) -> String {
+    match (start, step) {
+        (_, 1) => "*".to_string(),
+        (start, step) if step >= max => start.to_string(),
+        (0, step) => format!("*/{step}"),
+        (start, step) => (start..max)
+            .step_by(step as usize)
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cron(expr: &str) -> Schedule {
+        Schedule::Cron(expr.to_string())
+    }
+
+    #[test]
+    fn parses_interval_units() {
+        assert_eq!("seconds".parse(), Ok(IntervalUnit::Seconds));
+        assert_eq!("minutes".parse(), Ok(IntervalUnit::Minutes));
+        assert!("hours".parse::<IntervalUnit>().is_err());
+        assert!("".parse::<IntervalUnit>().is_err());
+    }
+
+    #[test]
+    fn aligns_dividing_intervals_to_the_clock() {
+        assert_eq!(
+            Schedule::from_interval(30, IntervalUnit::Seconds, 0),
+            Ok(cron("*/30 * * * * *"))
+        );
+        assert_eq!(
+            Schedule::from_interval(5, IntervalUnit::Minutes, 0),
+            Ok(cron("0 */5 * * * *"))
+        );
+        assert_eq!(
+            Schedule::from_interval(60, IntervalUnit::Minutes, 0),
+            Ok(cron("0 0 * * * *"))
+        );
+        assert_eq!(
+            Schedule::from_interval(1440, IntervalUnit::Minutes, 0),
+            Ok(cron("0 0 0 * * *"))
+        );
+    }
+
+    #[test]
+    fn shifts_aligned_intervals_by_the_offset() {
+        // Offsets wrap to the interval
+        assert_eq!(
+            Schedule::from_interval(30, IntervalUnit::Seconds, 45),
+            Ok(cron("15,45 * * * * *"))
+        );
+        assert_eq!(
+            Schedule::from_interval(15, IntervalUnit::Minutes, 61),
+            Ok(cron("1 1,16,31,46 * * * *"))
+        );
+        assert_eq!(
+            Schedule::from_interval(360, IntervalUnit::Minutes, 7200),
+            Ok(cron("0 0 2,8,14,20 * * *"))
+        );
+    }
+
+    #[test]
+    fn repeats_other_intervals() {
+        assert_eq!(
+            Schedule::from_interval(7, IntervalUnit::Minutes, 1000),
+            Ok(Schedule::Every {
+                period: Duration::from_secs(420),
+                offset: Duration::from_secs(160),
+            })
+        );
+        assert_eq!(
+            Schedule::from_interval(45, IntervalUnit::Seconds, 0),
+            Ok(Schedule::Every {
+                period: Duration::from_secs(45),
+                offset: Duration::ZERO,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_intervals() {
+        assert!(Schedule::from_interval(0, IntervalUnit::Minutes, 0).is_err());
+        assert!(Schedule::from_interval(-5, IntervalUnit::Seconds, 0).is_err());
+        assert!(
+            Schedule::from_interval(MIN_INTERVAL_SECONDS as i32 - 1, IntervalUnit::Seconds, 0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn stagger_offsets_are_stable() {
+        assert_eq!(stagger_offset("mon_a"), stagger_offset("mon_a"));
+        assert_ne!(stagger_offset("mon_a"), stagger_offset("mon_b"));
+    }
+
+    #[test]
+    fn finds_the_period() {
+        let every = Schedule::Every {
+            period: Duration::from_secs(420),
+            offset: Duration::from_secs(100),
+        };
+
+        assert_eq!(every.period(), Some(Duration::from_secs(420)));
+        assert_eq!(
+            cron("*/30 * * * * *").period(),
+            Some(Duration::from_secs(30))
+        );
+        // The shortest gap of an irregular expression
+        assert_eq!(
+            cron("0 0 9,17 * * *").period(),
+            Some(Duration::from_secs(8 * 3600))
+        );
+    }
+
+    #[test]
+    fn keeps_the_delay_below_the_period() {
+        let every = Schedule::Every {
+            period: Duration::from_secs(45),
+            offset: Duration::from_secs(40),
+        };
+
+        assert_eq!(
+            every.delay_with(Duration::from_secs(4)),
+            Duration::from_secs(44)
+        );
+        assert_eq!(
+            every.delay_with(Duration::from_secs(10)),
+            Duration::from_secs(44)
+        );
+        assert_eq!(
+            cron("*/30 * * * * *").delay_with(Duration::from_secs(12)),
+            Duration::from_secs(12)
+        );
+    }
+
+    #[test]
+    fn clamps_jitter_to_half_the_period() {
+        let schedule = cron("*/30 * * * * *");
+
+        assert_eq!(schedule.max_jitter(Duration::ZERO), Duration::ZERO);
+        assert_eq!(
+            schedule.max_jitter(Duration::from_secs(10)),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            schedule.max_jitter(Duration::from_secs(300)),
+            Duration::from_secs(15)
+        );
+    }
+
+    #[test]
+    fn validates_cron_expressions_like_the_scheduler() {
+        assert_eq!(
+            Schedule::from_cron("*/5 * * * *"),
+            Ok(cron("0 */5 * * * *"))
+        );
+        assert_eq!(
+            Schedule::from_cron("30 0 9 * * MON-FRI"),
+            Ok(cron("30 0 9 * * MON-FRI"))
+        );
+        assert!(Schedule::from_cron("61 * * * * *").is_err());
+        assert!(Schedule::from_cron("every day").is_err());
+
+        for expr in ["0 0 12 1 * MON", "0 0 0 L * *", "0 0 0 * * 5#2"] {
+            assert_eq!(
+                Schedule::from_cron(expr).is_ok(),
+                tokio_cron_scheduler::Job::new_async(expr, |_, _| Box::pin(async {})).is_ok(),
+                "{expr}"
+            );
+        }
+    }
+}
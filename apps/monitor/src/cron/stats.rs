@@ -0,0 +1,141 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::Instant,
+};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// The last run of every job, by job ID
+static RUNS: Lazy<Mutex<HashMap<Uuid, JobRun>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// How a job run ended.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunResult {
+    /// Every address family of the monitor could be reached
+    Up,
+    /// At least one address family of the monitor couldn't be reached
+    Down,
+    /// There was nothing to do, like when the previous check was still running
+    Skipped,
+    /// The check couldn't run, like for an unknown monitor type
+    Failed,
+    /// The event job moved the event to its next status
+    Completed,
+}
+
+/// When a job last ran, for how long and how it ended.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRun {
+    pub started_at: DateTime<Utc>,
+    /// In milliseconds
+    pub duration: u64,
+    pub result: RunResult,
+}
+
+/// Measures a job run, call [`Run::finish`] once it's over.
+pub struct Run {
+    job_id: Uuid,
+    started_at: DateTime<Utc>,
+    start: Instant,
+}
+
+impl Run {
+    pub fn start(job_id: Uuid) -> Self {
+        Run {
+            job_id,
+            started_at: Utc::now(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Record the run as the last one of its job.
+    pub fn finish(self, result: RunResult) {
+        let run = JobRun {
+            started_at: self.started_at,
+            duration: self
+                .start
+                .elapsed()
+                .as_millis()
+                .try_into()
+                .unwrap_or(u64::MAX),
+            result,
+        };
+
+        RUNS.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(self.job_id, run);
+    }
+}
+
+/// The last run of a job, if it ran since this instance started.
+pub fn last_run(job_id: &Uuid) -> Option<JobRun> {
+    RUNS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(job_id)
+        .cloned()
+}
+
+/// Forget the runs of jobs that don't exist anymore.
+pub fn prune(jobs: &HashSet<Uuid>) {
+    RUNS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|job_id, _| jobs.contains(job_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pruning drops the runs of every job it isn't given, so the tests take turns
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn records_the_last_run_of_a_job() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let job_id = Uuid::new_v4();
+        assert!(last_run(&job_id).is_none());
+
+        Run::start(job_id).finish(RunResult::Up);
+        let first = last_run(&job_id).unwrap();
+        assert!(matches!(first.result, RunResult::Up));
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        Run::start(job_id).finish(RunResult::Skipped);
+        let second = last_run(&job_id).unwrap();
+        assert!(matches!(second.result, RunResult::Skipped));
+        assert!(second.started_at > first.started_at);
+    }
+
+    #[test]
+    fn measures_how_long_a_run_took() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let job_id = Uuid::new_v4();
+
+        let run = Run::start(job_id);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        run.finish(RunResult::Completed);
+
+        assert!(last_run(&job_id).unwrap().duration >= 20);
+    }
+
+    #[test]
+    fn prunes_runs_of_removed_jobs() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let (kept, removed) = (Uuid::new_v4(), Uuid::new_v4());
+
+        Run::start(kept).finish(RunResult::Down);
+        Run::start(removed).finish(RunResult::Failed);
+
+        let mut jobs: HashSet<Uuid> = RUNS.lock().unwrap().keys().copied().collect();
+        jobs.remove(&removed);
+        prune(&jobs);
+
+        assert!(last_run(&kept).is_some());
+        assert!(last_run(&removed).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     sync::{Arc, OnceLock},
     time::SystemTime,
 };
@@ -10,7 +11,12 @@ use tokio_cron_scheduler::JobScheduler;
 use uuid::Uuid;
 
 use crate::{
-    monitors::health::{load_registry, TrackedIncident},
+    cron::cluster::owns,
+    events::status::EventStatus,
+    monitors::{
+        get::get_monitors,
+        health::{load_registry, TrackedIncident},
+    },
     EVENT_REGISTRY, INCID_REGISTRY, MON_REGISTRY, POOL, SCHED,
 };
 
@@ -20,12 +26,18 @@ pub struct MonitorJobMetadata {
     pub cron_expr: String,
     pub monitor_id: String,
     pub created_at: SystemTime,
+    /// If the job runs on the faster schedule used while the monitor is failing
+    pub failing: bool,
+    /// The [`Monitor::fingerprint`](crate::monitors::get::Monitor::fingerprint) of the copy of the monitor the job checks
+    pub fingerprint: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct EventJobMetadata {
     pub id: Uuid,
     pub event_id: String,
+    /// The status the job moves the event to
+    pub transition: EventStatus,
     pub created_at: SystemTime,
 }
 
@@ -72,7 +84,8 @@ pub async fn start() {
     load_registry().await;
 }
 
-/// Load all the monitors from the database and create a ping cron job for them
+/// Load all the monitors from the database and create a ping cron job for them.
+/// Monitors and events led by another instance, or that already have a job, are skipped.
 pub async fn load_jobs() {
     let mon_reg = match MON_REGISTRY.get() {
         Some(reg) => reg,
@@ -98,28 +111,41 @@ pub async fn load_jobs() {
         }
     };
 
+    let (scheduled_monitors, scheduled_events) = {
+        let mon_reg = mon_reg.lock().await;
+        let event_reg = event_reg.lock().await;
+        (
+            mon_reg
+                .iter()
+                .map(|jmd| jmd.monitor_id.clone())
+                .collect::<HashSet<_>>(),
+            event_reg
+                .iter()
+                .map(|jmd| jmd.event_id.clone())
+                .collect::<HashSet<_>>(),
+        )
+    };
+
     let pool = POOL.clone();
-    let mon_tasks = match query!("SELECT * FROM monitors ORDER BY created_at DESC")
-        .fetch_all(&pool)
-        .await
-    {
-        Ok(query) => query.into_iter().map(|row| {
-            tokio::spawn(async move {
-                match crate::monitors::create_job(
-                    row.id,
-                    row.url,
-                    row.r#type,
-                    row.interval.to_string(),
-                    sched.lock().await,
-                    mon_reg.lock().await,
-                )
-                .await
-                {
-                    Ok(_) => info!("Created monitor job"),
-                    Err(e) => error!("Failed to create monitor job: {e:?}"),
-                }
-            })
-        }),
+    let mon_tasks = match get_monitors().await {
+        Ok(monitors) => monitors
+            .into_iter()
+            .filter(|monitor| !monitor.paused)
+            .filter(|monitor| owns(&monitor.id) && !scheduled_monitors.contains(&monitor.id))
+            .map(|monitor| {
+                tokio::spawn(async move {
+                    match crate::monitors::create_job(
+                        monitor,
+                        sched.lock().await,
+                        mon_reg.lock().await,
+                    )
+                    .await
+                    {
+                        Ok(_) => info!("Created monitor job"),
+                        Err(e) => error!("Failed to create monitor job: {e:?}"),
+                    }
+                })
+            }),
         Err(e) => {
             error!("Failed to fetch monitors: {e:?}");
             return;
@@ -127,24 +153,27 @@ pub async fn load_jobs() {
     };
 
     let event_tasks =
-        match query!("SELECT * FROM events WHERE completed is false AND auto_complete is true ORDER BY created_at DESC")
+        match query!("SELECT * FROM events WHERE status IN ('scheduled', 'in_progress') ORDER BY created_at DESC")
             .fetch_all(&pool)
             .await
         {
-            Ok(query) => query.into_iter().map(|row| {
-                tokio::spawn(async move {
-                    match crate::events::create_job(
-                        row.id,
-                        sched.lock().await,
-                        event_reg.lock().await,
-                    )
-                    .await
-                    {
-                        Ok(_) => info!("Created event job"),
-                        Err(e) => error!("Failed to create event job: {e:?}"),
-                    }
-                })
-            }),
+            Ok(query) => query
+                .into_iter()
+                .filter(|row| owns(&row.id) && !scheduled_events.contains(&row.id))
+                .map(|row| {
+                    tokio::spawn(async move {
+                        match crate::events::create_job(
+                            row.id,
+                            sched.lock().await,
+                            event_reg.lock().await,
+                        )
+                        .await
+                        {
+                            Ok(_) => info!("Created event job"),
+                            Err(e) => error!("Failed to create event job: {e:?}"),
+                        }
+                    })
+                }),
             Err(e) => {
                 error!("Failed to fetch events: {e:?}");
                 return;
@@ -165,3 +194,60 @@ pub async fn load_jobs() {
             .ok();
     }
 }
+
+/// Remove the jobs of monitors and events this instance no longer leads,
+/// along with their tracked incidents.
+pub async fn unload_jobs() {
+    let (sched, mon_reg, event_reg, incid_reg) = match (
+        SCHED.get(),
+        MON_REGISTRY.get(),
+        EVENT_REGISTRY.get(),
+        INCID_REGISTRY.get(),
+    ) {
+        (Some(sched), Some(mon_reg), Some(event_reg), Some(incid_reg)) => {
+            (sched, mon_reg, event_reg, incid_reg)
+        }
+        _ => {
+            error!("Failed to get scheduler or registries");
+            return;
+        }
+    };
+
+    let sched = sched.lock().await;
+
+    let mut ids = {
+        let mut mon_reg = mon_reg.lock().await;
+        let ids = mon_reg
+            .iter()
+            .filter(|jmd| !owns(&jmd.monitor_id))
+            .map(|jmd| jmd.id)
+            .collect::<Vec<_>>();
+        mon_reg.retain(|jmd| owns(&jmd.monitor_id));
+        ids
+    };
+
+    {
+        let mut event_reg = event_reg.lock().await;
+        ids.extend(
+            event_reg
+                .iter()
+                .filter(|jmd| !owns(&jmd.event_id))
+                .map(|jmd| jmd.id),
+        );
+        event_reg.retain(|jmd| owns(&jmd.event_id));
+    }
+
+    for id in ids.iter() {
+        if let Err(e) = sched.remove(id).await {
+            error!("Failed to remove job {id}: {e}");
+        }
+    }
+
+    // The new leader loads these from the database
+    incid_reg
+        .lock()
+        .await
+        .retain(|incident| owns(&incident.monitor_id));
+
+    info!("Removed {} job(s)", ids.len());
+}
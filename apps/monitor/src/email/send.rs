@@ -15,6 +15,7 @@ pub enum TemplateOptions {
     // AutoIncidentMonitoring = 2,
     // AutoIncidnetFailing = 3,
     // AutoIncidnetResolve = 4,
+    ContentChange = 5,
 }
 
 pub async fn send_email(
@@ -30,6 +31,7 @@ pub async fn send_email(
         // TemplateOptions::AutoIncidentMonitoring => "auto-incid-recovering",
         // TemplateOptions::AutoIncidnetFailing => "auto-incid-failing",
         // TemplateOptions::AutoIncidnetResolve => "auto-incid-resolve",
+        TemplateOptions::ContentChange => "content-change",
     };
 
     if !config.email.enabled {
@@ -78,14 +80,13 @@ pub async fn send_email(
     match content {
         Some(content) => {
             // For each key in the replace_values, use the key to replace a placeholder in the template with the value
-            let mut email_content = content;
-            if let Some(values) = replace_values {
-                for (key, value) in values.as_object().unwrap_or(&serde_json::Map::new()).iter() {
-                    let placeholder = key.to_string();
-                    email_content =
-                        email_content.replace(&placeholder, value.as_str().unwrap_or(""));
-                }
-            }
+            let email_content = match replace_values {
+                Some(values) => fill(
+                    &content,
+                    values.as_object().unwrap_or(&serde_json::Map::new()),
+                ),
+                None => content,
+            };
 
             let email = Message::builder()
                 .from(format!("Miru <{from}>").parse().unwrap())
@@ -95,6 +96,7 @@ pub async fn send_email(
                     // TemplateOptions::AutoIncidentMonitoring => "Some monitors are recovering",
                     // TemplateOptions::AutoIncidnetFailing => "Some monitors are failing",
                     // TemplateOptions::AutoIncidnetResolve => "Some monitors are back online",
+                    TemplateOptions::ContentChange => "A monitored page has changed",
                 })
                 .body(email_content)
                 .unwrap();
@@ -148,3 +150,64 @@ pub async fn send_email(
         None => Err(format!("Failed to find template for: {template_name:?}")),
     }
 }
+
+/// Replace the placeholders of a template with their values in a single pass.
+/// Values aren't searched again, so page text containing a placeholder like `monitorName` is kept as is.
+fn fill(template: &str, values: &serde_json::Map<String, serde_json::Value>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+
+    loop {
+        // The earliest placeholder wins, the longest one if several start at the same place
+        let next = values
+            .iter()
+            .filter(|(key, _)| !key.is_empty())
+            .filter_map(|(key, value)| rest.find(key.as_str()).map(|at| (at, key, value)))
+            .min_by_key(|(at, key, _)| (*at, std::cmp::Reverse(key.len())));
+
+        match next {
+            Some((at, key, value)) => {
+                filled.push_str(&rest[..at]);
+                filled.push_str(value.as_str().unwrap_or(""));
+                rest = &rest[at + key.len()..];
+            }
+            None => {
+                filled.push_str(rest);
+                return filled;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn fill_json(template: &str, values: serde_json::Value) -> String {
+        fill(template, values.as_object().unwrap())
+    }
+
+    #[test]
+    fn fills_placeholders() {
+        assert_eq!(
+            fill_json(
+                "monitorName changed, see viewUrl (monitorName)",
+                json!({ "monitorName": "API", "viewUrl": "https://miru.test" })
+            ),
+            "API changed, see https://miru.test (API)"
+        );
+    }
+
+    #[test]
+    fn leaves_placeholders_in_values_alone() {
+        assert_eq!(
+            fill_json(
+                "<pre>changeDiff</pre> monitorName",
+                json!({ "changeDiff": "+ welcome to monitorName at viewUrl", "monitorName": "Docs", "viewUrl": "x" })
+            ),
+            "<pre>+ welcome to monitorName at viewUrl</pre> Docs"
+        );
+    }
+}
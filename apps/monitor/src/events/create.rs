@@ -1,19 +1,35 @@
-use std::{sync::Arc, time::Duration};
+use std::{future::Future, pin::Pin, sync::Arc};
 
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use log::{error, info};
 use sqlx::query;
 use tokio::sync::MutexGuard;
 use tokio_cron_scheduler::{Job, JobScheduler};
 use uuid::Uuid;
 
-use crate::{cron::worker::EventJobMetadata, POOL};
+use crate::{
+    cron::{
+        stats::{Run, RunResult},
+        worker::EventJobMetadata,
+    },
+    events::{
+        self,
+        recurrence::Recurrence,
+        status::{transition, EventStatus},
+    },
+    shutdown::Task,
+    EVENT_REGISTRY, POOL, SCHED,
+};
 
+/// Schedule the transitions of an event, to in progress when it starts and to completed when it ends
+/// if it completes automatically. Recurring events go back to scheduled after each occurrence, until the last one.
+/// Transitions that should already have happened, like after a restart in the middle of the event,
+/// are made straight away.
 pub async fn create_job<'a>(
     event_id: String,
     sched: MutexGuard<'a, JobScheduler>,
     mut registry: MutexGuard<'a, Vec<EventJobMetadata>>,
-) -> Result<Uuid, Box<dyn std::error::Error>> {
+) -> Result<(), Box<dyn std::error::Error>> {
     info!("Creating event job with ID: {event_id}");
 
     let pool = POOL.clone();
@@ -29,72 +45,230 @@ pub async fn create_job<'a>(
         }
     };
 
-    // Figure out the duration for the job by adding the duration to the start time and date
-    let datetime_utc = Utc.from_utc_datetime(&event.started_at);
-    let scheduled_time = datetime_utc + chrono::Duration::minutes(event.duration as i64);
+    let status = EventStatus::from(event.status);
+
+    if status.is_over() {
+        return Err(format!("Event {event_id} is already {status}").into());
+    }
 
+    let recurrence = match event.recurrence.as_deref() {
+        Some(rule) => Some(rule.parse::<Recurrence>()?),
+        None => None,
+    };
+
+    // Every occurrence of a recurring event ends on its own
+    let auto_complete = event.auto_complete || recurrence.is_some();
+
+    let first = Utc.from_utc_datetime(&event.started_at);
+    let duration = chrono::Duration::minutes(event.duration as i64);
     let now = Utc::now();
-    let delay_duration = scheduled_time.signed_duration_since(now);
-
-    info!(
-        "Scheduled time: {}, Now: {}, Delay duration: {} seconds",
-        scheduled_time,
-        now,
-        delay_duration.num_seconds()
-    );
-
-    if delay_duration < chrono::Duration::zero() {
-        match query!("UPDATE events SET completed = true WHERE id = $1", event_id)
-            .fetch_one(&pool)
-            .await
-        {
-            Ok(_) => {
-                return Err(format!(
-                    "Event {event_id} marked as completed due to scheduled time"
-                )
-                .into());
-            }
-            Err(e) => {
-                error!("Failed to mark event as completed: {e}");
-                return Err(Box::new(e));
+
+    // Recurring events follow the occurrence going on now or the next one
+    let starts_at = match &recurrence {
+        Some(recurrence) => recurrence.occurrence(first, duration, now),
+        None => Some(first),
+    };
+
+    let current = match starts_at {
+        Some(starts_at) => catch_up(starts_at, auto_complete.then(|| starts_at + duration), now),
+        None => EventStatus::Completed,
+    };
+
+    match starts_at {
+        Some(starts_at) => info!(
+            "Event {event_id} starts at {starts_at} and ends at {}, it's {current}",
+            starts_at + duration
+        ),
+        None => info!("Every occurrence of event {event_id} is over"),
+    }
+
+    if current != status {
+        transition(&event_id, current).await?;
+        info!("Event {event_id} caught up from {status} to {current}");
+    }
+
+    let starts_at = match starts_at {
+        Some(starts_at) if current != EventStatus::Completed => starts_at,
+        _ => return Ok(()),
+    };
+
+    if current == EventStatus::Scheduled {
+        add_job(
+            &event_id,
+            starts_at,
+            EventStatus::InProgress,
+            &sched,
+            &mut registry,
+        )
+        .await?;
+    }
+
+    if auto_complete {
+        // After an occurrence, recurring events wait for the next one
+        let after = match &recurrence {
+            Some(recurrence) if recurrence.has_next(first, duration, starts_at) => {
+                EventStatus::Scheduled
             }
-        }
+            _ => EventStatus::Completed,
+        };
+
+        add_job(
+            &event_id,
+            starts_at + duration,
+            after,
+            &sched,
+            &mut registry,
+        )
+        .await?;
     }
 
-    let duration = Duration::from_secs(delay_duration.num_seconds() as u64);
+    Ok(())
+}
 
-    let event_id_clone = Arc::new(event.id.clone());
+/// The status an event starting at `start` should be in at `now`, `end` being when it completes
+/// automatically, if it does. Events without an end stay in progress until they're completed by hand.
+fn catch_up(start: DateTime<Utc>, end: Option<DateTime<Utc>>, now: DateTime<Utc>) -> EventStatus {
+    match end {
+        _ if now < start => EventStatus::Scheduled,
+        Some(end) if now >= end => EventStatus::Completed,
+        _ => EventStatus::InProgress,
+    }
+}
+
+/// Add a job moving an event to a status at the given time to the scheduler and the registry.
+async fn add_job(
+    event_id: &str,
+    at: DateTime<Utc>,
+    status: EventStatus,
+    sched: &JobScheduler,
+    registry: &mut Vec<EventJobMetadata>,
+) -> Result<Uuid, Box<dyn std::error::Error>> {
+    let delay = (at - Utc::now()).to_std().unwrap_or_default();
+    let event_id_clone = Arc::new(event_id.to_string());
 
-    let job = match Job::new_one_shot_async(duration, move |_, _| {
+    let job = Job::new_one_shot_async(delay, move |job_id, _| {
         let event_id: Arc<String> = Arc::clone(&event_id_clone);
-        Box::pin({
-            async move {
-                info!("Marking event {event_id} as completed");
-                let pool = POOL.clone();
-                let id = event_id.to_string();
-                match query!("UPDATE events SET completed = true WHERE id = $1", id)
-                    .execute(&pool)
-                    .await
-                {
-                    Ok(_) => info!("Event {event_id} marked as completed"),
-                    Err(e) => error!("Failed to mark event {event_id} as completed: {e}"),
+        Box::pin(async move {
+            // The scheduler works in whole seconds and can fire a moment early,
+            // which would make the event look like it's still in its last occurrence
+            if let Ok(early) = (at - Utc::now()).to_std() {
+                tokio::time::sleep(early).await;
+            }
+
+            let _task = match Task::start() {
+                Some(task) => task,
+                None => return,
+            };
+
+            let run = Run::start(job_id);
+            info!("Marking event {event_id} as {status}");
+
+            match transition(&event_id, status).await {
+                Ok(true) => {
+                    info!("Event {event_id} marked as {status}");
+                    run.finish(RunResult::Completed);
+
+                    // Only recurring events go back to scheduled, once an occurrence ended
+                    if status == EventStatus::Scheduled {
+                        schedule_next(&event_id).await;
+                    }
+                }
+                Ok(false) => {
+                    info!("Event {event_id} is over or already {status}, leaving it");
+                    run.finish(RunResult::Skipped);
+                }
+                Err(e) => {
+                    error!("Failed to mark event {event_id} as {status}: {e}");
+                    run.finish(RunResult::Failed);
                 }
             }
         })
-    }) {
-        Ok(job) => job,
-        Err(e) => return Err(e.into()),
-    };
-
-    sched.add(job.clone()).await.unwrap();
+    })?;
 
-    let job_id = job.guid();
+    let job_id = sched.add(job).await?;
 
     registry.push(EventJobMetadata {
         id: job_id,
         event_id: event_id.to_string(),
+        transition: status,
         created_at: std::time::SystemTime::now(),
     });
 
-    Ok(Uuid::new_v4())
+    info!("Scheduled event {event_id} to be {status} at {at}");
+
+    Ok(job_id)
+}
+
+/// Schedule the next occurrence of a recurring event, replacing the jobs of the last one.
+/// Boxed since the completion job `update_job` schedules calls back into it once the occurrence ends.
+fn schedule_next(event_id: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+    Box::pin(async move {
+        let (sched, reg) = match (SCHED.get(), EVENT_REGISTRY.get()) {
+            (Some(sched), Some(reg)) => (sched, reg),
+            _ => {
+                error!("Failed to get scheduler or event registry");
+                return;
+            }
+        };
+
+        if let Err(e) =
+            events::update_job(event_id.to_string(), sched.lock().await, reg.lock().await).await
+        {
+            error!("Failed to schedule the next occurrence of event {event_id}: {e}");
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn waits_for_events_in_the_future() {
+        assert_eq!(
+            catch_up(at(10, 0), Some(at(11, 0)), at(9, 0)),
+            EventStatus::Scheduled
+        );
+        assert_eq!(catch_up(at(10, 0), None, at(9, 59)), EventStatus::Scheduled);
+    }
+
+    #[test]
+    fn catches_up_in_the_middle_of_an_event() {
+        assert_eq!(
+            catch_up(at(10, 0), Some(at(11, 0)), at(10, 30)),
+            EventStatus::InProgress
+        );
+        assert_eq!(
+            catch_up(at(10, 0), Some(at(11, 0)), at(10, 0)),
+            EventStatus::InProgress
+        );
+    }
+
+    #[test]
+    fn completes_events_that_already_ended() {
+        assert_eq!(
+            catch_up(at(10, 0), Some(at(11, 0)), at(11, 0)),
+            EventStatus::Completed
+        );
+        assert_eq!(
+            catch_up(at(10, 0), Some(at(11, 0)), at(23, 0)),
+            EventStatus::Completed
+        );
+    }
+
+    #[test]
+    fn keeps_manual_events_in_progress_after_their_duration() {
+        assert_eq!(
+            catch_up(at(10, 0), None, at(10, 30)),
+            EventStatus::InProgress
+        );
+        assert_eq!(
+            catch_up(at(10, 0), None, at(23, 0)),
+            EventStatus::InProgress
+        );
+    }
 }
@@ -1,5 +1,9 @@
 pub mod create;
+pub mod recurrence;
+pub mod remove;
+pub mod status;
 pub mod update;
 
 pub use create::create_job;
+pub use remove::remove_job;
 pub use update::update_job;
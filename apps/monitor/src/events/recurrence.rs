@@ -0,0 +1,287 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Months, NaiveDate, NaiveDateTime, Utc};
+
+/// How often a recurring event repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// When a recurring event repeats, parsed from an RRULE-style string like
+/// `FREQ=WEEKLY;INTERVAL=2;COUNT=10` stored in the `recurrence` column of the `events` table.
+/// Only `FREQ` (daily, weekly or monthly), `INTERVAL`, `COUNT` and `UNTIL` are supported,
+/// occurrences always start at the same time of day, weekday or day of the month as the event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    /// How many occurrences there are, counting the first one
+    pub count: Option<u32>,
+    /// No occurrence starts after this
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = match part.split_once('=') {
+                Some(pair) => pair,
+                None => return Err(format!("Invalid recurrence rule part {part}")),
+            };
+
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(format!("Unsupported recurrence frequency {other}")),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = match value.parse::<u32>() {
+                        Ok(interval) if interval > 0 => interval,
+                        _ => return Err(format!("Invalid recurrence interval {value}")),
+                    }
+                }
+                "COUNT" => {
+                    count = match value.parse::<u32>() {
+                        Ok(count) if count > 0 => Some(count),
+                        _ => return Err(format!("Invalid recurrence count {value}")),
+                    }
+                }
+                "UNTIL" => until = Some(parse_until(value)?),
+                other => return Err(format!("Unsupported recurrence rule part {other}")),
+            }
+        }
+
+        if count.is_some() && until.is_some() {
+            return Err("A recurrence rule can't have both COUNT and UNTIL".to_string());
+        }
+
+        match frequency {
+            Some(frequency) => Ok(Recurrence {
+                frequency,
+                interval,
+                count,
+                until,
+            }),
+            None => Err("A recurrence rule needs a FREQ".to_string()),
+        }
+    }
+}
+
+/// Parse an `UNTIL` value, either a UTC date-time like `20261231T235959Z` or a date like `20261231`.
+fn parse_until(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Ok(datetime.and_utc());
+    }
+
+    match NaiveDate::parse_from_str(value, "%Y%m%d") {
+        Ok(date) => Ok(date.and_hms_opt(23, 59, 59).unwrap_or_default().and_utc()),
+        Err(_) => Err(format!("Invalid recurrence end {value}")),
+    }
+}
+
+impl Recurrence {
+    /// When the nth occurrence starts, the first one being the event itself.
+    /// Returns `None` once the count or end date is past.
+    pub fn nth(&self, first: DateTime<Utc>, n: u32) -> Option<DateTime<Utc>> {
+        if self.count.is_some_and(|count| n >= count) {
+            return None;
+        }
+
+        let steps = n.checked_mul(self.interval)?;
+
+        // Always count from the first occurrence, so monthly events that had to move to the end
+        // of a shorter month go back to their own day after it
+        let start = match self.frequency {
+            Frequency::Daily => first.checked_add_signed(Duration::days(steps as i64))?,
+            Frequency::Weekly => first.checked_add_signed(Duration::weeks(steps as i64))?,
+            Frequency::Monthly => first.checked_add_months(Months::new(steps))?,
+        };
+
+        match self.until {
+            Some(until) if start > until => None,
+            _ => Some(start),
+        }
+    }
+
+    /// The start of the occurrence going on at `now`, or of the next one.
+    /// Returns `None` once every occurrence is over.
+    pub fn occurrence(
+        &self,
+        first: DateTime<Utc>,
+        duration: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let mut n = 0;
+
+        loop {
+            let start = self.nth(first, n)?;
+
+            if start + duration > now {
+                return Some(start);
+            }
+
+            n += 1;
+        }
+    }
+
+    /// If there's another occurrence after the one starting at `start`.
+    pub fn has_next(&self, first: DateTime<Utc>, duration: Duration, start: DateTime<Utc>) -> bool {
+        self.occurrence(first, duration, start + duration).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn rule(rule: &str) -> Recurrence {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!(
+            rule("RRULE:FREQ=WEEKLY;INTERVAL=2;COUNT=10"),
+            Recurrence {
+                frequency: Frequency::Weekly,
+                interval: 2,
+                count: Some(10),
+                until: None,
+            }
+        );
+        assert_eq!(rule("freq=daily").frequency, Frequency::Daily);
+        assert_eq!(
+            rule("FREQ=MONTHLY;UNTIL=20261231").until,
+            Some(Utc.with_ymd_and_hms(2026, 12, 31, 23, 59, 59).unwrap())
+        );
+        assert_eq!(
+            rule("FREQ=MONTHLY;UNTIL=20261231T120000Z").until,
+            Some(at(2026, 12, 31, 12, 0))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for invalid in [
+            "",
+            "INTERVAL=2",
+            "FREQ=YEARLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=0",
+            "FREQ=DAILY;COUNT=2;UNTIL=20261231",
+            "FREQ=DAILY;BYDAY=MO",
+            "FREQ=DAILY;UNTIL=tomorrow",
+            "FREQ",
+        ] {
+            assert!(invalid.parse::<Recurrence>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn clamps_to_the_end_of_shorter_months() {
+        let monthly = rule("FREQ=MONTHLY");
+        let first = at(2027, 1, 31, 9, 0);
+
+        assert_eq!(monthly.nth(first, 1), Some(at(2027, 2, 28, 9, 0)));
+        // Back to the 31st after the short month
+        assert_eq!(monthly.nth(first, 2), Some(at(2027, 3, 31, 9, 0)));
+        assert_eq!(monthly.nth(first, 3), Some(at(2027, 4, 30, 9, 0)));
+        // Leap years keep the 29th
+        assert_eq!(monthly.nth(first, 13), Some(at(2028, 2, 29, 9, 0)));
+    }
+
+    #[test]
+    fn steps_by_the_interval() {
+        let first = at(2026, 10, 19, 22, 0);
+
+        assert_eq!(
+            rule("FREQ=DAILY;INTERVAL=3").nth(first, 1),
+            Some(at(2026, 10, 22, 22, 0))
+        );
+        assert_eq!(
+            rule("FREQ=WEEKLY;INTERVAL=2").nth(first, 2),
+            Some(at(2026, 11, 16, 22, 0))
+        );
+    }
+
+    #[test]
+    fn count_includes_the_first_occurrence() {
+        let thrice = rule("FREQ=DAILY;COUNT=3");
+        let first = at(2026, 10, 19, 9, 0);
+
+        assert_eq!(thrice.nth(first, 0), Some(first));
+        assert_eq!(thrice.nth(first, 2), Some(at(2026, 10, 21, 9, 0)));
+        assert_eq!(thrice.nth(first, 3), None);
+    }
+
+    #[test]
+    fn until_includes_an_occurrence_starting_right_at_it() {
+        let first = at(2026, 10, 19, 9, 0);
+
+        let until = rule("FREQ=DAILY;UNTIL=20261021T090000Z");
+        assert_eq!(until.nth(first, 2), Some(at(2026, 10, 21, 9, 0)));
+        assert_eq!(until.nth(first, 3), None);
+
+        let before = rule("FREQ=DAILY;UNTIL=20261021T085959Z");
+        assert_eq!(before.nth(first, 2), None);
+    }
+
+    #[test]
+    fn finds_the_occurrence_at_its_boundaries() {
+        let daily = rule("FREQ=DAILY;COUNT=2");
+        let first = at(2026, 10, 19, 9, 0);
+        let hour = Duration::hours(1);
+
+        // Before, at the start of and during the first occurrence
+        assert_eq!(
+            daily.occurrence(first, hour, at(2026, 10, 18, 0, 0)),
+            Some(first)
+        );
+        assert_eq!(daily.occurrence(first, hour, first), Some(first));
+        assert_eq!(
+            daily.occurrence(first, hour, at(2026, 10, 19, 9, 59)),
+            Some(first)
+        );
+
+        // Right as it ends, the next one is up
+        let second = at(2026, 10, 20, 9, 0);
+        assert_eq!(daily.occurrence(first, hour, first + hour), Some(second));
+
+        // Once the last one ended there's nothing left
+        assert_eq!(daily.occurrence(first, hour, second + hour), None);
+    }
+
+    #[test]
+    fn knows_the_last_occurrence() {
+        let daily = rule("FREQ=DAILY;COUNT=2");
+        let first = at(2026, 10, 19, 9, 0);
+        let hour = Duration::hours(1);
+
+        assert!(daily.has_next(first, hour, first));
+        assert!(!daily.has_next(first, hour, at(2026, 10, 20, 9, 0)));
+        assert!(rule("FREQ=WEEKLY").has_next(first, hour, first));
+    }
+}
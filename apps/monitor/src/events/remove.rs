@@ -0,0 +1,49 @@
+use log::info;
+use tokio::sync::MutexGuard;
+use tokio_cron_scheduler::JobScheduler;
+
+use crate::{
+    cron::worker::EventJobMetadata,
+    events::status::{transition, EventStatus},
+};
+
+/// Remove the jobs of an event and mark it as cancelled if it isn't over yet,
+/// returning how many jobs were removed. Deleted events are simply left out.
+pub async fn remove_job(
+    event_id: &str,
+    sched: MutexGuard<'_, JobScheduler>,
+    mut registry: MutexGuard<'_, Vec<EventJobMetadata>>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let removed = remove_jobs(event_id, &sched, &mut registry).await?;
+
+    if transition(event_id, EventStatus::Cancelled).await? {
+        info!("Event {event_id} marked as cancelled");
+    }
+
+    Ok(removed)
+}
+
+/// Remove the jobs of an event without changing its status, returning how many were removed.
+pub async fn remove_jobs(
+    event_id: &str,
+    sched: &JobScheduler,
+    registry: &mut Vec<EventJobMetadata>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let ids = registry
+        .iter()
+        .filter(|jmd| jmd.event_id == event_id)
+        .map(|jmd| jmd.id)
+        .collect::<Vec<_>>();
+
+    for id in ids.iter() {
+        if let Err(e) = sched.remove(id).await {
+            return Err(e.to_string().into());
+        }
+
+        registry.retain(|job| job.id != *id);
+
+        info!("Removed event job {id}");
+    }
+
+    Ok(ids.len())
+}
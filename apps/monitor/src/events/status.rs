@@ -0,0 +1,75 @@
+use std::fmt;
+
+use serde::Serialize;
+use sqlx::query;
+
+use crate::POOL;
+
+/// Where an event is in its lifecycle, stored in the `status` column of the `events` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventStatus {
+    Scheduled,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+impl EventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventStatus::Scheduled => "scheduled",
+            EventStatus::InProgress => "in_progress",
+            EventStatus::Completed => "completed",
+            EventStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// If the event completed or was cancelled, after which it never changes again.
+    pub fn is_over(&self) -> bool {
+        matches!(self, EventStatus::Completed | EventStatus::Cancelled)
+    }
+
+    /// If an event in this status still has transitions to schedule, `ends` being if it ends on its own,
+    /// like recurring events or ones that complete automatically. Others stay in progress until completed by hand.
+    pub fn is_pending(&self, ends: bool) -> bool {
+        match self {
+            EventStatus::Scheduled => true,
+            EventStatus::InProgress => ends,
+            EventStatus::Completed | EventStatus::Cancelled => false,
+        }
+    }
+}
+
+impl From<String> for EventStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "in_progress" => EventStatus::InProgress,
+            "completed" => EventStatus::Completed,
+            "cancelled" => EventStatus::Cancelled,
+            _ => EventStatus::Scheduled,
+        }
+    }
+}
+
+impl fmt::Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str().replace('_', " "))
+    }
+}
+
+/// Move an event to a status, unless it's already over.
+/// Returns if the event changed, `completed` is kept in sync for the dashboard.
+pub async fn transition(event_id: &str, status: EventStatus) -> Result<bool, sqlx::Error> {
+    let pool = POOL.clone();
+
+    let result = query!(
+        "UPDATE events SET status = $2, completed = ($2 = 'completed') WHERE id = $1 AND status IN ('scheduled', 'in_progress') AND status <> $2",
+        event_id,
+        status.as_str()
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
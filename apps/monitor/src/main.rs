@@ -1,3 +1,4 @@
+mod agent;
 mod config;
 mod cron;
 mod email;
@@ -6,6 +7,7 @@ mod monitors;
 mod notifs;
 mod ping;
 mod routes;
+mod shutdown;
 
 use std::{
     env,
@@ -30,8 +32,13 @@ use crate::{
     config::{read_config_from_file, start_watcher, MiruConfig},
     cron::worker::EventJobMetadata,
     routes::{
+        agents::{agent_monitors_service, agent_pings_service},
         config_service, create_event_job_service, create_monitor_job_service,
-        cron::events::update_event_job_service, remove_monitor_job_service,
+        cron::{
+            events::{list_event_jobs_service, remove_event_job_service, update_event_job_service},
+            monitors::list_monitor_jobs_service,
+        },
+        pause_monitor_job_service, remove_monitor_job_service, resume_monitor_job_service,
         update_monitor_job_service,
     },
 };
@@ -82,6 +89,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Err(e) => warn!("{e}"),
     }
 
+    // Agents only check monitors and report back, they don't need the database
+    if agent::enabled() {
+        info!("Starting as a probe agent...");
+        return agent::run().await;
+    }
+
     info!("Connecting to database...");
 
     let pool = POOL.clone();
@@ -97,11 +110,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Starting cron worker...");
     cron::worker::start().await;
-    info!("Loading cron jobs...");
-    cron::worker::load_jobs().await;
+    // When clustering, jobs are loaded once this instance leads a shard
+    if !cron::cluster::start() {
+        info!("Loading cron jobs...");
+        cron::worker::load_jobs().await;
+    }
+
+    cron::reconcile::start();
 
     info!("Starting server...");
-    match HttpServer::new(|| {
+    let server = HttpServer::new(|| {
         let cors = Cors::default()
             .allow_any_origin()
             .allow_any_method()
@@ -116,17 +134,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .service(create_monitor_job_service)
             .service(remove_monitor_job_service)
             .service(update_monitor_job_service)
+            .service(pause_monitor_job_service)
+            .service(resume_monitor_job_service)
+            .service(list_monitor_jobs_service)
             // Cron - Events
             .service(create_event_job_service)
             .service(update_event_job_service)
+            .service(remove_event_job_service)
+            .service(list_event_jobs_service)
             .service(registry_service)
             .service(config_service)
+            // Probe agents
+            .service(agent_monitors_service)
+            .service(agent_pings_service)
             .default_service(web::to(not_found_service))
     })
     .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
-    {
+    // Signals are handled by `shutdown`, so jobs are drained before the server stops
+    .disable_signals()
+    .run();
+
+    shutdown::listen(server.handle());
+
+    match server.await {
         Ok(_) => Ok(()),
         Err(e) => Err(e.into()),
     }
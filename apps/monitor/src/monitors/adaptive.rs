@@ -0,0 +1,100 @@
+use std::{future::Future, pin::Pin};
+
+use log::{error, info};
+
+use crate::{
+    config::get_config,
+    cron::schedule::Schedule,
+    monitors::{create::add_job, get::get_monitor},
+    MON_REGISTRY, SCHED,
+};
+
+/// Switch a monitor's job between its normal schedule and the faster one used while it's failing
+/// (`incidents.auto.failing_interval`), so incidents open and resolve quickly.
+/// The new job is added before the old one is removed, so the monitor is never left without a job,
+/// and it shares the monitor's running flag so a check the old job started isn't overlapped.
+/// Only monitors with an open incident are switched, a single failed check doesn't reschedule anything.
+///
+/// The future is boxed because the job it reschedules runs this function itself,
+/// which would otherwise make the compiler go in circles proving the job future is `Send`.
+pub fn set_failing(
+    monitor_id: &str,
+    failing: bool,
+) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+    Box::pin(async move {
+        let interval = get_config().incidents.auto.failing_interval;
+
+        if failing && interval == 0 {
+            return;
+        }
+
+        let (sched, reg) = match (SCHED.get(), MON_REGISTRY.get()) {
+            (Some(sched), Some(reg)) => (sched, reg),
+            _ => {
+                error!("Failed to get scheduler or monitor registry");
+                return;
+            }
+        };
+
+        // Most checks don't change anything, so look at the registry before locking the scheduler
+        let current = {
+            let reg = reg.lock().await;
+            reg.iter()
+                .find(|jmd| jmd.monitor_id == monitor_id)
+                .map(|jmd| jmd.failing)
+        };
+
+        // Paused and removed monitors don't have a job to reschedule
+        if current.is_none() || current == Some(failing) {
+            return;
+        }
+
+        let monitor = match get_monitor(monitor_id).await {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                error!("Error fetching monitor: {e}");
+                return;
+            }
+        };
+
+        let schedule = match failing {
+            true => match Schedule::for_failing_monitor(&monitor, interval) {
+                Some(schedule) => schedule,
+                None => return,
+            },
+            false => match Schedule::for_monitor(&monitor) {
+                Ok(schedule) => schedule,
+                Err(e) => {
+                    error!("Failed to reschedule monitor {monitor_id}: {e}");
+                    return;
+                }
+            },
+        };
+
+        // Same order as the routes, the scheduler first and then the registry
+        let sched = sched.lock().await;
+        let mut registry = reg.lock().await;
+
+        // The job could have been replaced while we were waiting for the locks
+        let old = match registry.iter().find(|jmd| jmd.monitor_id == monitor_id) {
+            Some(jmd) if jmd.failing != failing => jmd.id,
+            _ => return,
+        };
+
+        if let Err(e) = add_job(monitor, schedule, failing, &sched, &mut registry).await {
+            error!("Failed to reschedule monitor {monitor_id}: {e}");
+            return;
+        }
+
+        if let Err(e) = sched.remove(&old).await {
+            error!("Failed to remove job {old}: {e}");
+        }
+
+        registry.retain(|job| job.id != old);
+
+        match failing {
+            true => info!("Monitor {monitor_id} is failing, checking it every {interval}s"),
+            false => info!("Monitor {monitor_id} recovered, back to its normal schedule"),
+        }
+    })
+}
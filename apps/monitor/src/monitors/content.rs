@@ -0,0 +1,220 @@
+use log::{error, info};
+use monitor::get_app_url;
+use regex::Regex;
+use similar::TextDiff;
+use sqlx::query;
+
+use crate::{
+    email::send::{send_email, TemplateOptions},
+    monitors::{
+        get::Monitor,
+        health::{get_member_emails, get_workspace},
+        maintenance::under_maintenance,
+    },
+    notifs::discord::send::send_discord_message,
+    POOL,
+};
+
+/// How many lines of the diff are included in notifications
+static MAX_DIFF_LINES: usize = 20;
+/// How many characters of the diff are included in notifications
+static MAX_DIFF_CHARS: usize = 1500;
+
+/// Compile a monitor's ignore regexes, jobs do it once when they're created instead of on every check.
+/// Fails on the first invalid regex, so a monitor can't be saved with one.
+pub fn compile_ignore(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern)
+                .map_err(|e| format!("Invalid content ignore regex {pattern:?}: {e}"))
+        })
+        .collect()
+}
+
+/// Strip the parts of a body matching the monitor's ignore regexes (timestamps, CSRF tokens, ...),
+/// so they don't count as a change.
+fn normalize(body: &str, ignore: &[Regex]) -> String {
+    let mut body = body.to_string();
+
+    for re in ignore {
+        body = re.replace_all(&body, "").to_string();
+    }
+
+    body
+}
+
+/// Hash a normalized body as a hex encoded SHA-256.
+fn hash(body: &str) -> String {
+    openssl::sha::sha256(body.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Build a short unified diff of what changed, truncated to fit in a notification.
+fn diff_excerpt(old: &str, new: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let diff = diff.unified_diff().context_radius(1).to_string();
+
+    let lines = diff
+        .lines()
+        .filter(|line| !line.starts_with("@@"))
+        .collect::<Vec<_>>();
+
+    let excerpt = lines
+        .iter()
+        .take(MAX_DIFF_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let truncated = lines.len() > MAX_DIFF_LINES || excerpt.chars().count() > MAX_DIFF_CHARS;
+    let mut excerpt = excerpt.chars().take(MAX_DIFF_CHARS).collect::<String>();
+
+    if truncated {
+        excerpt.push_str("\n...");
+    }
+
+    excerpt
+}
+
+/// Escape a string so it can be placed inside an email template.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Compare a response body with the last one seen for the monitor and notify the workspace if it changed.
+/// The first body seen is stored as the baseline. Returns the hash of the normalized body.
+pub async fn check_content(monitor: &Monitor, body: &str) -> Option<String> {
+    let pool = POOL.clone();
+
+    let body = normalize(body, &monitor.content_patterns);
+    let hash = hash(&body);
+
+    let tracked = match query!(
+        "SELECT hash, body FROM tracked_contents WHERE monitor_id = $1",
+        monitor.id
+    )
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(tracked) => tracked,
+        Err(e) => {
+            error!("Error fetching tracked content: {e}");
+            return Some(hash);
+        }
+    };
+
+    if tracked.as_ref().is_some_and(|tracked| tracked.hash == hash) {
+        return Some(hash);
+    }
+
+    if let Err(e) = query!(
+        "INSERT INTO tracked_contents (monitor_id, hash, body) VALUES ($1, $2, $3)
+        ON CONFLICT (monitor_id) DO UPDATE SET hash = $2, body = $3, updated_at = now()",
+        monitor.id,
+        hash,
+        body
+    )
+    .execute(&pool)
+    .await
+    {
+        error!("Error storing tracked content: {e}");
+    }
+
+    match tracked {
+        Some(tracked) => {
+            info!("Content of {} has changed", monitor.url);
+
+            // Changes during maintenance are expected, the new content just becomes the baseline
+            match under_maintenance(&monitor.id).await {
+                true => info!("Not notifying, {} is under maintenance", monitor.url),
+                false => notify(monitor, &diff_excerpt(&tracked.body, &body)).await,
+            }
+        }
+        None => info!("Stored content baseline for {}", monitor.url),
+    }
+
+    Some(hash)
+}
+
+/// Send `content-change` emails and Discord notifications to the monitor's workspace.
+async fn notify(monitor: &Monitor, diff: &str) {
+    let workspace = match get_workspace(&monitor.workspace_id).await {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            error!("{e}");
+            return;
+        }
+    };
+
+    let url = format!(
+        "{}/admin/{}/monitors/{}",
+        get_app_url(),
+        workspace.slug,
+        monitor.id
+    );
+
+    match get_member_emails(&monitor.workspace_id).await {
+        Ok(emails) => {
+            for email in emails.iter() {
+                match send_email(
+                    TemplateOptions::ContentChange,
+                    email,
+                    Some(serde_json::json!({
+                        "monitorName": monitor.name,
+                        "changeDiff": escape_html(diff),
+                        "viewUrl": url
+                    })),
+                )
+                .await
+                {
+                    Ok(_) => info!("Sent content change email to {email}"),
+                    Err(e) => error!("{e}"),
+                }
+            }
+        }
+        Err(e) => error!("{e}"),
+    }
+
+    let content = format!(
+        "## {} has changed\n\n```diff\n{}\n```\n[[View Monitor]({})]",
+        monitor.name,
+        diff.replace("```", "'''"),
+        url
+    );
+
+    match send_discord_message(workspace, content).await {
+        Ok(_) => info!("Sent content change Discord notification(s)"),
+        Err(e) => error!("Failed to send Discord notification(s): {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_ignored_parts() {
+        let ignore = compile_ignore(&[
+            r"\d{2}:\d{2}:\d{2}".to_string(),
+            r#"csrf="[^"]*""#.to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            normalize(r#"<p csrf="a1b2">Updated 12:34:56</p>"#, &ignore),
+            "<p >Updated </p>"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_regexes() {
+        let err = compile_ignore(&["ok".to_string(), "(unclosed".to_string()]).unwrap_err();
+        assert!(err.contains("(unclosed"));
+    }
+}
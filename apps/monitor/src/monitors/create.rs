@@ -1,225 +1,472 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use log::{error, info};
-use monitor::generate_id;
-use sqlx::query;
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
 use tokio::sync::MutexGuard;
 use tokio_cron_scheduler::{Job, JobScheduler};
 use uuid::Uuid;
 
 use crate::{
+    agent,
     config::get_config,
-    cron::{self, worker::MonitorJobMetadata},
-    monitors::health::{check_health, resolve_incident},
-    ping::{http_ping, tcp_ping},
-    POOL,
+    cron::{
+        limits,
+        schedule::Schedule,
+        stats::{Run, RunResult},
+        worker::MonitorJobMetadata,
+    },
+    monitors::{
+        content::{check_content, compile_ignore},
+        get::Monitor,
+        health::{check_health, resolve_incident},
+        locations::recovered,
+        maintenance::under_maintenance,
+        pings::{insert_skipped, PingAttempt, PingRecord},
+    },
+    ping::{http_ping, tcp_ping, AddressFamily, PingOptions},
+    shutdown::Task,
 };
 
+/// If a check is running, by monitor ID. Shared by every job of a monitor, so a job replacing another one
+/// (an update, the failing interval, the reconciler) still waits for the check the old one started.
+static RUNNING: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The running flag of a monitor, shared by all of its jobs.
+pub fn running_flag(monitor_id: &str) -> Arc<AtomicBool> {
+    let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    Arc::clone(running.entry(monitor_id.to_string()).or_default())
+}
+
+/// Drop the running flag of a monitor that no longer has a job, unless its last check is still going.
+pub fn forget_running(monitor_id: &str) {
+    let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    if running
+        .get(monitor_id)
+        .is_some_and(|flag| !flag.load(Ordering::Acquire))
+    {
+        running.remove(monitor_id);
+    }
+}
+
 pub async fn create_job<'a>(
-    monitor_id: String,
-    url: String,
-    r#type: String,
-    interval: String,
+    monitor: Monitor,
     sched: MutexGuard<'a, JobScheduler>,
     mut registry: MutexGuard<'a, Vec<MonitorJobMetadata>>,
 ) -> Result<Uuid, Box<dyn std::error::Error>> {
-    info!("Creating monitor job with ID: {monitor_id}");
-
-    let interval = match interval.to_string().as_str() {
-        "1" => cron::ONE_MINUTE_CRON.to_string(),
-        "5" => cron::FIVE_MINUTE_CRON.to_string(),
-        "10" => cron::TEN_MINUTE_CRON.to_string(),
-        "30" => cron::THIRTY_MINUTE_CRON.to_string(),
-        "60" => cron::ONE_HOUR_CRON.to_string(),
-        _ => cron::TEN_MINUTE_CRON.to_string(),
-    };
+    info!("Creating monitor job with ID: {}", monitor.id);
+
+    let schedule = Schedule::for_monitor(&monitor)?;
+
+    add_job(monitor, schedule, false, &sched, &mut registry).await
+}
 
+/// Add a job checking a monitor on the given schedule to the scheduler and the registry.
+pub async fn add_job(
+    monitor: Monitor,
+    schedule: Schedule,
+    failing: bool,
+    sched: &JobScheduler,
+    registry: &mut Vec<MonitorJobMetadata>,
+) -> Result<Uuid, Box<dyn std::error::Error>> {
     info!(
-        "Creating job for {url} with type {type} and schedule {interval:?}"
+        "Creating job for {} with type {} and schedule {schedule}",
+        monitor.url, monitor.r#type
     );
 
-    let url = Arc::new(url);
-    let r#type = Arc::new(r#type);
-    let monitor_id = Arc::new(monitor_id);
-    let monitor_id_clone = monitor_id.clone();
+    let monitor_id = monitor.id.clone();
+    let fingerprint = monitor.fingerprint();
+    let monitor = limit_retries(monitor, &schedule);
+    let monitor = Arc::new(Monitor {
+        content_patterns: compile_ignore(&monitor.content_ignore)?,
+        // A check may not take longer than the interval, or the next ones would be skipped
+        options: PingOptions {
+            timeout: schedule.period(),
+            ..monitor.options
+        },
+        ..monitor
+    });
+    let running = running_flag(&monitor_id);
+    let job_schedule = schedule.clone();
 
-    let job = match Job::new_async(interval.clone(), move |_, _| {
-        let url = Arc::clone(&url);
-        let r#type = Arc::clone(&r#type);
-        let monitor_id = Arc::clone(&monitor_id);
+    let run = move |job_id, _| {
+        let monitor = Arc::clone(&monitor);
+        let running = Arc::clone(&running);
+        let delay = job_schedule.delay();
         Box::pin({
             async move {
-                let pool = POOL.clone();
-
-                match r#type.as_str() {
-                    "http" => {
-                        let result = match http_ping(url.to_string()).await {
-                            Ok(result) => {
-                                info!(
-                                    "Successfully pinged {} at {} with status {} in {}ms",
-                                    url,
-                                    chrono::Utc::now(),
-                                    result.status,
-                                    result.latency
-                                );
-
-                                let config = get_config();
-
-                                if config.incidents.auto.enabled {
-                                    resolve_incident(monitor_id.to_string()).await;
-                                }
-
-                                result
-                            }
-                            Err(e) => {
-                                error!(
-                                    "Failed to ping {} at {} with error {} in {}ms: {}",
-                                    url,
-                                    chrono::Utc::now(),
-                                    e.response.status,
-                                    e.response.latency,
-                                    e.error
-                                );
-
-                                let query = query!(
-                                    "INSERT INTO pings (id, monitor_id, type, success, status, latency, headers) VALUES ($1, $2, $3, $4, $5, $6, $7)",
-                                    generate_id(),
-                                    monitor_id.to_string(),
-                                    "http",
-                                    false,
-                                    e.response.status,
-                                    e.response.latency,
-                                    serde_json::to_value(e.response.headers).unwrap_or(serde_json::Value::Null)
-                                )
-                                .execute(&pool)
-                                .await;
-
-                                if let Err(e) = query {
-                                    error!("Error inserting failed ping: {e}");
-                                }
-
-                                let config = get_config();
-
-                                if config.incidents.auto.enabled {
-                                    check_health(monitor_id.to_string(), url.to_string()).await;
-                                }
-
-                                return;
-                            }
-                        };
-
-                        let headers_json =
-                            serde_json::to_value(result.headers).unwrap_or(serde_json::Value::Null);
-
-                        let query = query!(
-                            "INSERT INTO pings (id, monitor_id, type, success, status, latency, headers) VALUES ($1, $2, $3, $4, $5, $6, $7)",
-                            generate_id(),
-                            monitor_id.to_string(),
-                            "http",
-                            result.success,
-                            result.status,
-                            result.latency,
-                            headers_json
-                        )
-                        .execute(&pool)
-                        .await;
-
-                        match query {
-                            Ok(_) => {}
-                            Err(e) => {
-                                error!("Error inserting successful ping: {e}");
-                            }
-                        }
-                    }
-                    "tcp" => {
-                        let result = match tcp_ping(url.to_string()).await {
-                            Ok(result) => {
-                                info!(
-                                    "Successfully pinged {} at {} with success {} in {}ms",
-                                    url,
-                                    chrono::Utc::now(),
-                                    result.success,
-                                    result.latency
-                                );
-
-                                let config = get_config();
-
-                                if config.incidents.auto.enabled {
-                                    resolve_incident(monitor_id.to_string()).await;
-                                }
-
-                                result
-                            }
-                            Err(e) => {
-                                error!(
-                                    "Failed to ping {} at {}: {}",
-                                    url,
-                                    chrono::Utc::now(),
-                                    e.error
-                                );
-
-                                let query = query!(
-                                    "INSERT INTO pings (id, monitor_id, type, success, latency) VALUES ($1, $2, $3, $4, $5)",
-                                    generate_id(),
-                                    monitor_id.to_string(),
-                                    "tcp",
-                                    e.response.success,
-                                    e.response.latency
-                                )
-                                .execute(&pool)
-                                .await;
-
-                                if let Err(e) = query {
-                                    error!("Error inserting failed ping: {e}");
-                                }
-
-                                let config = get_config();
-
-                                if config.incidents.auto.enabled {
-                                    check_health(monitor_id.to_string(), url.to_string()).await;
-                                }
-
-                                return;
-                            }
-                        };
-
-                        let query = query!(
-                            "INSERT INTO pings (id, monitor_id, type, success, latency) VALUES ($1, $2, $3, $4, $5)",
-                            generate_id(),
-                            monitor_id.to_string(),
-                            "tcp",
-                            result.success,
-                            result.latency
-                        ).execute(&pool).await;
-
-                        match query {
-                            Ok(_) => {}
-                            Err(e) => {
-                                error!("Error inserting ping: {e}");
-                            }
-                        }
-                    }
-                    _ => {
-                        error!("Unknown type: {type}");
+                // Wait out the stagger offset and jitter first, a run that's only waiting isn't running yet
+                tokio::time::sleep(delay).await;
+
+                // Don't start new checks while shutting down, shutdown doesn't wait for the sleep above
+                let _task = match Task::start() {
+                    Some(task) => task,
+                    None => return,
+                };
+
+                let run = Run::start(job_id);
+
+                // A slow check must not overlap with itself, skip this run if the last one is still going
+                if running.swap(true, Ordering::AcqRel) {
+                    warn!(
+                        "Skipping check for {}, the previous one is still running",
+                        monitor.url
+                    );
+                    if let Err(e) = insert_skipped(&monitor.id).await {
+                        error!("Error inserting skipped check: {e}");
                     }
+                    run.finish(RunResult::Skipped);
+                    return;
                 }
+
+                let _guard = RunningGuard(running);
+                run.finish(run_check(&monitor).await);
             }
-        })
-    }) {
+        }) as Pin<Box<dyn Future<Output = ()> + Send>>
+    };
+
+    let job = match &schedule {
+        Schedule::Cron(expr) => Job::new_async(expr.as_str(), run),
+        Schedule::Every { period, .. } => Job::new_repeated_async(*period, run),
+    };
+
+    let job = match job {
         Ok(job) => job,
         Err(e) => return Err(e.into()),
     };
 
-    sched.add(job.clone()).await.unwrap();
+    sched.add(job.clone()).await?;
 
     let job_id = job.guid();
 
     registry.push(MonitorJobMetadata {
         id: job_id,
-        monitor_id: monitor_id_clone.to_string(),
-        cron_expr: interval.to_owned(),
+        monitor_id,
+        cron_expr: schedule.to_string(),
         created_at: std::time::SystemTime::now(),
+        failing,
+        fingerprint,
     });
 
     Ok(job_id)
 }
+
+/// Cap the retries of a monitor so the delays between them take at most half of the time between checks,
+/// leaving the other half for the attempts. A check that's still retrying when the next one fires gets it skipped.
+pub fn limit_retries(monitor: Monitor, schedule: &Schedule) -> Monitor {
+    let delay = monitor.retry_delay.max(0) as u128;
+    let retries = monitor.retries.max(0) as u128;
+
+    let budget = match schedule.period() {
+        Some(period) => period.as_millis() / 2,
+        None => return monitor,
+    };
+
+    if delay == 0 || delay * retries <= budget {
+        return monitor;
+    }
+
+    let limited = (budget / delay) as i32;
+
+    warn!(
+        "Retrying {} {} times {}ms apart would take more than half of its interval, only retrying {limited} times",
+        monitor.url, monitor.retries, monitor.retry_delay
+    );
+
+    Monitor {
+        retries: limited,
+        ..monitor
+    }
+}
+
+/// Clears a job's running flag when its check finishes, even if the check panicked.
+pub struct RunningGuard(pub Arc<AtomicBool>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Ping a monitor over each of its address families and update its incidents.
+/// Dual-stack monitors are only considered healthy when both families are reachable,
+/// so a failure on a single family is enough to open an incident.
+async fn run_check(monitor: &Monitor) -> RunResult {
+    let mut failed: Vec<AddressFamily> = vec![];
+    let mut reachable: Vec<AddressFamily> = vec![];
+
+    let maintenance = under_maintenance(&monitor.id).await;
+
+    if maintenance && get_config().maintenance.skip_checks {
+        info!("Skipping check for {}, it's under maintenance", monitor.url);
+        return RunResult::Skipped;
+    }
+
+    for options in monitor.options.expand() {
+        match ping(monitor, &options, maintenance).await {
+            Some(true) => reachable.push(options.address_family),
+            Some(false) => failed.push(options.address_family),
+            None => return RunResult::Failed,
+        }
+    }
+
+    let result = match failed.is_empty() {
+        true => RunResult::Up,
+        false => RunResult::Down,
+    };
+
+    let config = get_config();
+
+    // Incidents wait until the maintenance is over, so nobody is notified about it
+    if !config.incidents.auto.enabled || maintenance {
+        return result;
+    }
+
+    for address_family in reachable {
+        recovered(&monitor.id, address_family).await;
+    }
+
+    if failed.is_empty() {
+        resolve_incident(monitor.id.to_string()).await;
+    }
+
+    for address_family in failed {
+        check_health(
+            monitor.id.to_string(),
+            monitor.url.to_string(),
+            address_family,
+        )
+        .await;
+    }
+
+    result
+}
+
+/// Ping a monitor and store the result, failed pings being marked if the monitor is under maintenance.
+/// Returns if the monitor could be reached, or `None` if the monitor type is unknown.
+async fn ping(monitor: &Monitor, options: &PingOptions, maintenance: bool) -> Option<bool> {
+    let (record, reachable) = attempt(monitor, options).await?;
+
+    let record = PingRecord {
+        maintenance: maintenance && !record.success,
+        ..record
+    };
+
+    if let Err(e) = record.insert().await {
+        error!("Error inserting ping: {e}");
+    }
+
+    Some(reachable)
+}
+
+/// Ping a monitor, retrying failed checks as configured on the monitor.
+/// Only the last attempt is returned as a ping, earlier ones are kept on it for debugging.
+/// Returns the ping and if the monitor could be reached, or `None` if the monitor type is unknown.
+pub async fn attempt(monitor: &Monitor, options: &PingOptions) -> Option<(PingRecord, bool)> {
+    let mut retries: Vec<PingAttempt> = vec![];
+
+    let (record, reachable) = loop {
+        let (record, reachable, error) = probe(monitor, options).await?;
+
+        if record.success || retries.len() >= monitor.retries.max(0) as usize {
+            break (record, reachable);
+        }
+
+        warn!(
+            "Attempt {} for {} failed, retrying in {}ms",
+            retries.len() + 1,
+            monitor.url,
+            monitor.retry_delay
+        );
+
+        retries.push(PingAttempt {
+            status: record.status,
+            latency: record.latency,
+            error,
+            created_at: chrono::Utc::now(),
+        });
+
+        tokio::time::sleep(Duration::from_millis(monitor.retry_delay.max(0) as u64)).await;
+    };
+
+    // Scheduled pings don't keep the response body
+    let record = PingRecord {
+        body: None,
+        retries: match retries.is_empty() {
+            true => None,
+            false => serde_json::to_value(&retries).ok(),
+        },
+        ..record
+    };
+
+    Some((record, reachable))
+}
+
+/// Ping a monitor once.
+/// Returns the ping, if the monitor could be reached and the error if it couldn't,
+/// or `None` if the monitor type is unknown.
+async fn probe(
+    monitor: &Monitor,
+    options: &PingOptions,
+) -> Option<(PingRecord, bool, Option<String>)> {
+    let url = &monitor.url;
+
+    // Hold a slot while the monitor is pinged, it's given back before content checks send notifications
+    let (slot, waited) = limits::acquire(url).await;
+    let queue_latency = Some(waited.as_millis().try_into().unwrap_or(i32::MAX));
+
+    let (record, reachable, error) = match monitor.r#type.as_str() {
+        "http" => match with_slot(slot, http_ping(url.to_string(), options)).await {
+            Ok(result) => {
+                info!(
+                    "Successfully pinged {} at {} with status {} in {}ms",
+                    url,
+                    chrono::Utc::now(),
+                    result.status,
+                    result.latency
+                );
+
+                // Only compare real pages, an error page showing up isn't a content change.
+                // Agents leave content checks to the main instance, they have no database
+                let content_hash = match &result.body {
+                    Some(body) if monitor.content_check && result.success && !agent::enabled() => {
+                        check_content(monitor, body).await
+                    }
+                    _ => None,
+                };
+
+                let record = PingRecord {
+                    content_hash,
+                    ..PingRecord::from_http(&monitor.id, options, result)
+                };
+
+                (record, true, None)
+            }
+            Err(e) => {
+                error!(
+                    "Failed to ping {} at {} with error {} in {}ms: {}",
+                    url,
+                    chrono::Utc::now(),
+                    e.response.status,
+                    e.response.latency,
+                    e.error
+                );
+
+                (
+                    PingRecord::from_http(&monitor.id, options, e.response),
+                    false,
+                    Some(e.error),
+                )
+            }
+        },
+        "tcp" => match with_slot(slot, tcp_ping(url.to_string(), options)).await {
+            Ok(result) => {
+                info!(
+                    "Successfully pinged {} at {} with success {} in {}ms",
+                    url,
+                    chrono::Utc::now(),
+                    result.success,
+                    result.latency
+                );
+
+                (
+                    PingRecord::from_tcp(&monitor.id, options, result),
+                    true,
+                    None,
+                )
+            }
+            Err(e) => {
+                error!(
+                    "Failed to ping {} at {}: {}",
+                    url,
+                    chrono::Utc::now(),
+                    e.error
+                );
+
+                (
+                    PingRecord::from_tcp(&monitor.id, options, e.response),
+                    false,
+                    Some(e.error),
+                )
+            }
+        },
+        other => {
+            error!("Unknown type: {other}");
+            return None;
+        }
+    };
+
+    let record = PingRecord {
+        queue_latency,
+        ..record
+    };
+
+    Some((record, reachable, error))
+}
+
+/// Run a ping while holding a slot, giving it back as soon as the ping is done.
+async fn with_slot<T>(_slot: limits::Slot, ping: impl Future<Output = T>) -> T {
+    ping.await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_of_a_monitor_share_its_running_flag() {
+        let flag = running_flag("mon_shared");
+        let _guard = {
+            flag.store(true, Ordering::Release);
+            RunningGuard(Arc::clone(&flag))
+        };
+
+        assert!(running_flag("mon_shared").load(Ordering::Acquire));
+        assert!(!running_flag("mon_other").load(Ordering::Acquire));
+    }
+
+    fn retries(retries: i32, retry_delay: i32, period: u64) -> i32 {
+        let monitor = Monitor {
+            retries,
+            retry_delay,
+            ..Default::default()
+        };
+        let schedule = Schedule::Every {
+            period: Duration::from_secs(period),
+            offset: Duration::ZERO,
+        };
+
+        limit_retries(monitor, &schedule).retries
+    }
+
+    #[test]
+    fn keeps_retries_within_half_the_interval() {
+        assert_eq!(retries(3, 5000, 60), 3);
+        assert_eq!(retries(3, 10_000, 60), 3);
+        assert_eq!(retries(10, 10_000, 60), 3);
+        assert_eq!(retries(5, 30_000, 45), 0);
+        // Retrying right away doesn't wait at all
+        assert_eq!(retries(50, 0, 10), 50);
+    }
+
+    #[test]
+    fn keeps_the_flag_of_a_running_check() {
+        let flag = running_flag("mon_removed");
+        flag.store(true, Ordering::Release);
+        forget_running("mon_removed");
+        assert!(Arc::ptr_eq(&flag, &running_flag("mon_removed")));
+
+        flag.store(false, Ordering::Release);
+        forget_running("mon_removed");
+        assert!(!Arc::ptr_eq(&flag, &running_flag("mon_removed")));
+    }
+}
@@ -0,0 +1,40 @@
+use log::error;
+use sqlx::query;
+
+use crate::POOL;
+
+/// The open incident of a monitor this one depends on, if any of its parents is down.
+/// Parents that are themselves attached to their own parent's incident lead to that incident.
+pub async fn parent_incident(monitor_id: &str) -> Option<String> {
+    let pool = POOL.clone();
+
+    match query!(
+        "SELECT incidents.id FROM monitor_dependencies JOIN monitors_to_incidents ON monitors_to_incidents.monitor_id = monitor_dependencies.parent_id JOIN incidents ON incidents.id = monitors_to_incidents.incident_id WHERE monitor_dependencies.monitor_id = $1 AND incidents.resolved_at IS NULL ORDER BY incidents.started_at LIMIT 1",
+        monitor_id
+    )
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(row) => row.map(|row| row.id),
+        Err(e) => {
+            error!("Error checking parents of monitor {monitor_id}: {e}");
+            None
+        }
+    }
+}
+
+/// Link a failing monitor to its parent's incident as impacted.
+/// Returns `false` if it was already linked.
+pub async fn attach(monitor_id: &str, incident_id: &str) -> Result<bool, sqlx::Error> {
+    let pool = POOL.clone();
+
+    let result = query!(
+        "INSERT INTO monitors_to_incidents (monitor_id, incident_id, impacted) VALUES ($1, $2, true) ON CONFLICT DO NOTHING",
+        monitor_id,
+        incident_id
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
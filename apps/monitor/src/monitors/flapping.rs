@@ -0,0 +1,209 @@
+use std::{collections::HashMap, hash::Hash};
+
+use chrono::{Duration, Utc};
+use log::{error, info};
+use monitor::generate_id;
+use sqlx::query;
+
+use crate::{config::get_config, INCID_REGISTRY, POOL};
+
+/// How many times a monitor went up or down in the last `window` seconds.
+/// Locations and address families are counted separately so their pings don't get mixed up,
+/// the one that changed the most is used.
+pub async fn state_changes(monitor_id: &str, window: u64) -> Result<u64, sqlx::Error> {
+    let pool = POOL.clone();
+    let since = Utc::now().naive_utc() - Duration::seconds(window as i64);
+
+    let pings = query!(
+        "SELECT success, address_family, location FROM pings WHERE monitor_id = $1 AND created_at >= $2 AND maintenance IS false ORDER BY created_at",
+        monitor_id,
+        since
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(count_changes(pings.into_iter().map(|ping| {
+        ((ping.address_family, ping.location), ping.success)
+    })))
+}
+
+/// The most times the pings of one key, in order, went from up to down or back.
+fn count_changes<K: Clone + Eq + Hash>(pings: impl IntoIterator<Item = (K, bool)>) -> u64 {
+    let mut last = HashMap::new();
+    let mut changes = HashMap::new();
+
+    for (key, success) in pings {
+        if let Some(previous) = last.insert(key.clone(), success) {
+            if previous != success {
+                *changes.entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+
+    changes.into_values().max().unwrap_or(0)
+}
+
+/// If `changes` make a monitor flapping, with the lower bar for one that's already flapping.
+fn flaps(changes: u64, threshold: u64, flapping: bool) -> bool {
+    match flapping {
+        true => changes * 2 >= threshold,
+        false => changes >= threshold,
+    }
+}
+
+/// Where a flapping monitor is going, see [`set_flapping`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Flapping {
+    /// It started going up and down
+    Started,
+    /// It's stable again and up
+    SettledUp,
+    /// It's stable again but down
+    SettledDown,
+}
+
+/// If a monitor is flapping, going up and down at least `flapping_threshold` times within `flapping_window`.
+/// A monitor that's already flapping is only stable again once it changes less than half as often,
+/// so it doesn't go in and out of the flapping state.
+pub async fn is_flapping(monitor_id: &str, flapping: bool) -> bool {
+    let config = get_config().incidents.auto;
+
+    if config.flapping_threshold == 0 {
+        return false;
+    }
+
+    let changes = match state_changes(monitor_id, config.flapping_window).await {
+        Ok(changes) => changes,
+        Err(e) => {
+            error!("Error counting state changes of monitor {monitor_id}: {e}");
+            return flapping;
+        }
+    };
+
+    flaps(changes, config.flapping_threshold, flapping)
+}
+
+/// Move a tracked incident in or out of the flapping state.
+/// A flapping monitor holds its incident open without any new reports or notifications.
+/// Once it settles up the ping counts as the first success towards resolving the incident,
+/// once it settles down the incident is investigated again as if it had just opened.
+pub async fn set_flapping(incident_id: &str, monitor_name: &str, state: Flapping) {
+    let pool = POOL.clone();
+    let flapping = state == Flapping::Started;
+
+    match INCID_REGISTRY.get() {
+        Some(sched) => {
+            let mut incid_registry = sched.lock().await;
+            for tracked in incid_registry.iter_mut() {
+                if tracked.incident.id == incident_id {
+                    tracked.flapping = flapping;
+                    tracked.success = (state == Flapping::SettledUp) as u32;
+                    match state {
+                        Flapping::Started => {}
+                        Flapping::SettledUp => tracked.monitoring_created = true,
+                        Flapping::SettledDown => {
+                            tracked.monitoring_created = false;
+                            tracked.investigating_created = false;
+                        }
+                    }
+                }
+            }
+        }
+        None => error!("Failed to get incident registry"),
+    }
+
+    if let Err(e) = query!(
+        "UPDATE tracked_incidents SET flapping = $1 WHERE id = $2",
+        flapping,
+        incident_id
+    )
+    .execute(&pool)
+    .await
+    {
+        error!("Error updating tracked incident: {e}");
+    }
+
+    let (message, status) = match state {
+        Flapping::Started => {
+            info!("{monitor_name} is flapping, holding incident {incident_id} open");
+            (
+                format!("{monitor_name} keeps going up and down. This incident stays open until it's stable again."),
+                "identified",
+            )
+        }
+        Flapping::SettledUp => {
+            info!("{monitor_name} is no longer flapping");
+            (
+                format!("{monitor_name} is no longer flapping. Monitoring for stability."),
+                "monitoring",
+            )
+        }
+        Flapping::SettledDown => {
+            info!("{monitor_name} is no longer flapping, but down");
+            (
+                format!("{monitor_name} is no longer flapping, but still failing to ping. We're looking into this now."),
+                "investigating",
+            )
+        }
+    };
+
+    if let Err(e) = query!(
+        "INSERT INTO incident_reports (id, incident_id, message, status) VALUES ($1, $2, $3, $4)",
+        generate_id(),
+        incident_id,
+        message,
+        status
+    )
+    .execute(&pool)
+    .await
+    {
+        error!("Error creating incident report: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_changes_per_key() {
+        let pings = [
+            ("eu", true),
+            ("us", false),
+            ("eu", false),
+            ("us", false),
+            ("eu", true),
+            ("us", true),
+        ];
+
+        // eu went down and up, us only came up
+        assert_eq!(count_changes(pings), 2);
+    }
+
+    #[test]
+    fn interleaved_keys_dont_count_as_changes() {
+        let pings = [("eu", true), ("us", false), ("eu", true), ("us", false)];
+
+        assert_eq!(count_changes(pings), 0);
+        assert_eq!(count_changes::<&str>([]), 0);
+    }
+
+    #[test]
+    fn starts_flapping_at_the_threshold() {
+        assert!(!flaps(3, 4, false));
+        assert!(flaps(4, 4, false));
+    }
+
+    #[test]
+    fn stays_flapping_until_half_the_threshold() {
+        // Between half the threshold and the threshold the state sticks either way
+        assert!(!flaps(2, 4, false));
+        assert!(flaps(2, 4, true));
+        assert!(flaps(3, 4, true));
+
+        assert!(!flaps(1, 4, true));
+        // An odd threshold rounds the lower bar up
+        assert!(flaps(3, 5, true));
+        assert!(!flaps(2, 5, true));
+    }
+}
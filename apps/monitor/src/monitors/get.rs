@@ -0,0 +1,177 @@
+use log::error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::query_as;
+
+use crate::{cron::schedule::IntervalUnit, ping::PingOptions, POOL};
+
+/// A monitor and the settings its job needs.
+/// Probe agents get an [`AgentMonitor`](crate::agent::monitor::AgentMonitor) of it, so they can check it the same way.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Monitor {
+    pub id: String,
+    pub workspace_id: String,
+    pub name: String,
+    pub url: String,
+    pub r#type: String,
+    pub interval: i32,
+    /// If `interval` is in seconds or minutes
+    pub interval_unit: IntervalUnit,
+    /// A raw cron expression, used instead of the interval when set
+    pub cron: Option<String>,
+    /// How many times a failed check is retried before it counts as failed
+    pub retries: i32,
+    /// How long to wait between retries in milliseconds
+    pub retry_delay: i32,
+    /// Paused monitors aren't checked and don't open incidents
+    pub paused: bool,
+    pub options: PingOptions,
+    /// If the response body should be compared with the previous one
+    pub content_check: bool,
+    /// Regexes matching parts of the body to strip before comparing
+    pub content_ignore: Vec<String>,
+    /// `content_ignore` compiled by the monitor's job
+    #[serde(skip)]
+    pub content_patterns: Vec<Regex>,
+}
+
+impl Monitor {
+    /// A hash of every setting of the monitor, so a job can tell if it's checking an outdated copy.
+    pub fn fingerprint(&self) -> String {
+        let settings = serde_json::to_string(self).unwrap_or_default();
+
+        openssl::sha::sha256(settings.as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+struct MonitorRow {
+    id: String,
+    workspace_id: String,
+    name: String,
+    url: String,
+    r#type: String,
+    interval: i32,
+    interval_unit: String,
+    cron: Option<String>,
+    retries: i32,
+    retry_delay: i32,
+    paused: bool,
+    proxy: Option<String>,
+    address_family: String,
+    resolve: Option<String>,
+    dns_server: Option<String>,
+    content_check: bool,
+    content_ignore: Vec<String>,
+}
+
+/// Rows with an unknown interval unit are rejected instead of being checked on a default interval.
+impl TryFrom<MonitorRow> for Monitor {
+    type Error = String;
+
+    fn try_from(row: MonitorRow) -> Result<Self, Self::Error> {
+        let interval_unit = row
+            .interval_unit
+            .parse()
+            .map_err(|e| format!("Monitor {}: {e}", row.id))?;
+
+        Ok(Monitor {
+            id: row.id,
+            workspace_id: row.workspace_id,
+            name: row.name,
+            url: row.url,
+            r#type: row.r#type,
+            interval: row.interval,
+            interval_unit,
+            cron: row.cron,
+            retries: row.retries,
+            retry_delay: row.retry_delay,
+            paused: row.paused,
+            options: PingOptions {
+                proxy: row.proxy,
+                address_family: row.address_family.into(),
+                resolve: row.resolve,
+                dns_server: row.dns_server,
+                timeout: None,
+            },
+            content_check: row.content_check,
+            content_ignore: row.content_ignore,
+            content_patterns: vec![],
+        })
+    }
+}
+
+/// Get a single monitor from the database.
+pub async fn get_monitor(monitor_id: &str) -> Result<Monitor, sqlx::Error> {
+    let pool = POOL.clone();
+
+    query_as!(
+        MonitorRow,
+        "SELECT id, workspace_id, name, url, type, interval, interval_unit, cron, retries, retry_delay, paused, proxy, address_family, resolve, dns_server, content_check, content_ignore FROM monitors WHERE id = $1",
+        monitor_id
+    )
+    .fetch_one(&pool)
+    .await
+    .and_then(|row| Monitor::try_from(row).map_err(|e| sqlx::Error::Decode(e.into())))
+}
+
+/// Get all monitors from the database, newest first. Monitors that can't be read are logged and left out.
+pub async fn get_monitors() -> Result<Vec<Monitor>, sqlx::Error> {
+    let pool = POOL.clone();
+
+    query_as!(
+        MonitorRow,
+        "SELECT id, workspace_id, name, url, type, interval, interval_unit, cron, retries, retry_delay, paused, proxy, address_family, resolve, dns_server, content_check, content_ignore FROM monitors ORDER BY created_at DESC"
+    )
+    .fetch_all(&pool)
+    .await
+    .map(|rows| {
+        rows.into_iter()
+            .filter_map(|row| {
+                Monitor::try_from(row)
+                    .inspect_err(|e| error!("Skipping monitor: {e}"))
+                    .ok()
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(interval_unit: &str) -> MonitorRow {
+        MonitorRow {
+            id: "mon_a".to_string(),
+            workspace_id: "ws_a".to_string(),
+            name: "Example".to_string(),
+            url: "example.com".to_string(),
+            r#type: "http".to_string(),
+            interval: 5,
+            interval_unit: interval_unit.to_string(),
+            cron: None,
+            retries: 0,
+            retry_delay: 1000,
+            paused: false,
+            proxy: None,
+            address_family: "auto".to_string(),
+            resolve: None,
+            dns_server: None,
+            content_check: false,
+            content_ignore: vec![],
+        }
+    }
+
+    #[test]
+    fn parses_the_interval_unit() {
+        let monitor = Monitor::try_from(row("seconds")).unwrap();
+        assert_eq!(monitor.interval_unit, IntervalUnit::Seconds);
+    }
+
+    #[test]
+    fn rejects_unknown_interval_units() {
+        assert!(Monitor::try_from(row("fortnights")).is_err_and(|e| e.contains("fortnights")));
+    }
+}
@@ -6,7 +6,18 @@ use sqlx::query;
 use tokio::sync::MutexGuard;
 
 use crate::{
-    config::get_config, email::send::send_email, notifs::discord::send::send_discord,
+    config::get_config,
+    cron::cluster::owns,
+    email::send::send_email,
+    monitors::{
+        adaptive::set_failing,
+        dependencies::{attach, parent_incident},
+        flapping::{is_flapping, set_flapping, Flapping},
+        locations::{consensus, track_degradation},
+        maintenance::under_maintenance,
+    },
+    notifs::discord::send::send_discord,
+    ping::AddressFamily,
     INCID_REGISTRY, POOL,
 };
 
@@ -33,14 +44,17 @@ pub struct TrackedIncident {
     pub success: u32,
     pub monitoring_created: bool,
     pub investigating_created: bool,
+    /// If the monitor keeps going up and down, see [`is_flapping`]
+    pub flapping: bool,
 }
 
 /// Check the health of a monitor and create an incident if necessary.
-/// This function checks the last few (threshold) pings of a monitor and if all have failed,
-/// it creates a new incident in the database and adds it to the incident registry.
-pub async fn check_health(monitor_id: String, url: String) {
+/// This function checks the last few (threshold) pings of a monitor from each probe location and if
+/// enough locations saw them all fail, it creates a new incident in the database and adds it to the incident registry.
+/// Pings are only compared with pings made over the same address family, so dual-stack
+/// monitors open an incident when either family is down.
+pub async fn check_health(monitor_id: String, url: String, address_family: AddressFamily) {
     let pool = POOL.clone();
-    let config = get_config();
 
     let sched = match INCID_REGISTRY.get() {
         Some(sched) => sched,
@@ -72,9 +86,51 @@ pub async fn check_health(monitor_id: String, url: String) {
         }
     };
 
+    // Paused monitors never open incidents, and ones under maintenance wait until it's over
+    if monitor.paused || under_maintenance(&monitor_id).await {
+        return;
+    }
+
+    // Find out which probe locations see the monitor as down
+    let consensus = match consensus(&monitor_id, address_family).await {
+        Ok(consensus) => consensus,
+        Err(e) => {
+            error!("Error checking locations: {e}");
+            return;
+        }
+    };
+
+    track_degradation(&monitor_id, address_family, &consensus).await;
+
     if let Some(tracked_incid) = &tracked_incid {
+        // Check the monitor more often until its incident is resolved
+        set_failing(&monitor_id, true).await;
+
+        // With several locations, a single one failing doesn't mean the monitor is failing again
+        let failing = consensus.locations == 1 || consensus.reached();
+
+        // A flapping monitor holds its incident open without new reports until it's stable again,
+        // which it can be while down too
+        if tracked_incid.flapping {
+            if failing && !is_flapping(&monitor_id, true).await {
+                set_flapping(
+                    &tracked_incid.incident.id,
+                    &monitor.name,
+                    Flapping::SettledDown,
+                )
+                .await;
+            }
+            return;
+        }
+
+        if is_flapping(&monitor_id, false).await {
+            set_flapping(&tracked_incid.incident.id, &monitor.name, Flapping::Started).await;
+            return;
+        }
+
         // If the recovering monitor fails again, reset the success count and create investigating report
-        if !tracked_incid.investigating_created
+        if failing
+            && !tracked_incid.investigating_created
             && tracked_incid.monitoring_created
             && tracked_incid.success != 0
         {
@@ -109,36 +165,40 @@ pub async fn check_health(monitor_id: String, url: String) {
         }
     };
 
-    // Get the pings_threshold amount of pings and check if they have also failed
-    let threshold_pings = match query!(
-        "SELECT * FROM pings WHERE monitor_id = $1 ORDER BY created_at DESC LIMIT $2",
-        monitor_id.to_string(),
-        config.incidents.auto.pings_threshold as i64
-    )
-    .fetch_all(&pool)
-    .await
-    {
-        Ok(pings) => pings,
-        Err(e) => {
-            error!("Error fetching pings: {e}");
+    // Only open an incident when enough locations agree the monitor is down,
+    // fewer are recorded as a regional degradation instead
+    if consensus.reached() {
+        // When a monitor it depends on is down, the failure is part of that incident
+        if let Some(incid_id) = parent_incident(&monitor_id).await {
+            match attach(&monitor_id, &incid_id).await {
+                Ok(true) => {
+                    info!(
+                        "Attached {} to the incident {incid_id} of its parent",
+                        monitor.name
+                    );
+
+                    // Keep the incident's status, only note the monitor is affected too
+                    let report_query = query!(
+                        "INSERT INTO incident_reports (id, incident_id, message, status) SELECT $1, $2, $3, status FROM incident_reports WHERE incident_id = $4 ORDER BY timestamp DESC LIMIT 1",
+                        generate_id(),
+                        incid_id.clone(),
+                        format!("{} is also affected.", monitor.name),
+                        incid_id.clone()
+                    )
+                    .execute(&pool)
+                    .await;
+
+                    if let Err(e) = report_query {
+                        error!("Error creating incident report: {e}");
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => error!("Error linking monitor to its parent's incident: {e}"),
+            }
+
             return;
         }
-    };
 
-    if threshold_pings.len() < config.incidents.auto.pings_threshold as usize {
-        // If we don't have enough pings, we can't create an incident
-        return;
-    }
-
-    let last_threshold_pings = threshold_pings
-        .iter()
-        .take((config.incidents.auto.pings_threshold) as usize)
-        .collect::<Vec<_>>();
-
-    if last_threshold_pings
-        .iter()
-        .all(|ping| !ping.success)
-    {
         // If all previous pings have failed, create a new incident
         let incid_query = query!(
             "INSERT INTO incidents (id, title) VALUES ($1, $2) RETURNING *",
@@ -182,7 +242,14 @@ pub async fn check_health(monitor_id: String, url: String) {
                 "INSERT INTO incident_reports (id, incident_id, message, status) VALUES ($1, $2, $3, $4)",
                 generate_id(),
                 incid_id.clone(),
-                format!("It appears that {} is down. We are currently investigating this incident.", url.to_string()),
+                match (address_family, consensus.locations) {
+                    (AddressFamily::Ipv4, 1) => format!("It appears that {url} is down over IPv4. We are currently investigating this incident."),
+                    (AddressFamily::Ipv6, 1) => format!("It appears that {url} is down over IPv6. We are currently investigating this incident."),
+                    (_, 1) => format!("It appears that {url} is down. We are currently investigating this incident."),
+                    (AddressFamily::Ipv4, _) => format!("It appears that {url} is down over IPv4 from {}. We are currently investigating this incident.", consensus.describe()),
+                    (AddressFamily::Ipv6, _) => format!("It appears that {url} is down over IPv6 from {}. We are currently investigating this incident.", consensus.describe()),
+                    _ => format!("It appears that {url} is down from {}. We are currently investigating this incident.", consensus.describe()),
+                },
                 "investigating"
             ).execute(&pool).await;
 
@@ -198,59 +265,30 @@ pub async fn check_health(monitor_id: String, url: String) {
             success: 0,
             monitoring_created: false,
             investigating_created: false,
+            flapping: false,
         });
 
         // Sync the registry with the database
         sync_registry(incid_registry).await;
 
-        // Get all workspace member emails to send notifications
-        let workspace_user_ids = match query!(
-            "SELECT user_id FROM workspace_members WHERE workspace_id = $1",
-            monitor.workspace_id
-        )
-        .fetch_all(&pool)
-        .await
-        {
-            Ok(ids) => ids.into_iter().map(|id| id.user_id).collect::<Vec<_>>(),
-            Err(e) => {
-                error!("Error fetching workspace members: {e}");
-                return;
-            }
-        };
+        // Check the monitor more often until the incident is resolved
+        set_failing(&monitor_id, true).await;
 
-        // Get the emails of the users
-        let emails = match query!(
-            "SELECT email FROM public.user WHERE id = ANY($1) AND email_verified = true",
-            &workspace_user_ids
-        )
-        .fetch_all(&pool)
-        .await
-        {
-            Ok(emails) => emails
-                .into_iter()
-                .map(|email| email.email)
-                .collect::<Vec<_>>(),
+        // Get all workspace member emails to send notifications
+        let emails = match get_member_emails(&monitor.workspace_id).await {
+            Ok(emails) => emails,
             Err(e) => {
-                error!("Error fetching user emails: {e}");
+                error!("{e}");
                 return;
             }
         };
 
         let app_url = get_app_url();
 
-        let workspace: Workspace = match query!(
-            "SELECT * FROM workspaces WHERE id = $1",
-            monitor.workspace_id
-        )
-        .fetch_one(&pool)
-        .await
-        {
-            Ok(workspace) => Workspace {
-                id: workspace.id,
-                slug: workspace.slug,
-            },
+        let workspace = match get_workspace(&monitor.workspace_id).await {
+            Ok(workspace) => workspace,
             Err(e) => {
-                error!("Error fetching workspace: {e}");
+                error!("{e}");
                 return;
             }
         };
@@ -286,6 +324,52 @@ pub async fn check_health(monitor_id: String, url: String) {
     }
 }
 
+/// Get a workspace by its ID.
+pub async fn get_workspace(workspace_id: &str) -> Result<Workspace, String> {
+    let pool = POOL.clone();
+
+    match query!("SELECT * FROM workspaces WHERE id = $1", workspace_id)
+        .fetch_one(&pool)
+        .await
+    {
+        Ok(workspace) => Ok(Workspace {
+            id: workspace.id,
+            slug: workspace.slug,
+        }),
+        Err(e) => Err(format!("Error fetching workspace: {e}")),
+    }
+}
+
+/// Get the verified emails of all members of a workspace to send notifications to.
+pub async fn get_member_emails(workspace_id: &str) -> Result<Vec<String>, String> {
+    let pool = POOL.clone();
+
+    let workspace_user_ids = match query!(
+        "SELECT user_id FROM workspace_members WHERE workspace_id = $1",
+        workspace_id
+    )
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(ids) => ids.into_iter().map(|id| id.user_id).collect::<Vec<_>>(),
+        Err(e) => return Err(format!("Error fetching workspace members: {e}")),
+    };
+
+    match query!(
+        "SELECT email FROM public.user WHERE id = ANY($1) AND email_verified = true",
+        &workspace_user_ids
+    )
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(emails) => Ok(emails
+            .into_iter()
+            .map(|email| email.email)
+            .collect::<Vec<_>>()),
+        Err(e) => Err(format!("Error fetching user emails: {e}")),
+    }
+}
+
 /// Run a check when a ping is successful to see if there are any tracked incidents in the registry related to that monitor.
 /// If this is the first successful ping after an incident, create a `monitoring` report
 /// If this isn't the first successful ping, increase the `success` count by 1 and do nothing
@@ -309,7 +393,9 @@ pub async fn resolve_incident(monitor_id: String) {
     let tracked_incid = match tracked_incid {
         Some(inc) => inc,
         None => {
-            // If the monitor is not being tracked, we don't need to resolve anything
+            // If the monitor is not being tracked, we don't need to resolve anything,
+            // but its incident might have been resolved by hand while it was on the failing interval
+            set_failing(&monitor_id, false).await;
             return;
         }
     };
@@ -345,6 +431,7 @@ pub async fn resolve_incident(monitor_id: String) {
 
         // Sync the registry with the database
         sync_registry(incid_registry).await;
+        set_failing(&monitor_id, false).await;
         return;
     }
 
@@ -363,6 +450,49 @@ pub async fn resolve_incident(monitor_id: String) {
         }
     };
 
+    // Don't count towards resolving while enough other locations still see the monitor as down
+    let address_family = AddressFamily::from(monitor.address_family.clone());
+    let families = match address_family {
+        AddressFamily::Dual => vec![AddressFamily::Ipv4, AddressFamily::Ipv6],
+        family => vec![family],
+    };
+
+    for family in families {
+        match consensus(&monitor_id, family).await {
+            Ok(consensus) if consensus.reached() => {
+                info!(
+                    "Monitor {monitor_id} is still down from {}",
+                    consensus.describe()
+                );
+                return;
+            }
+            Ok(_) => {}
+            Err(e) => error!("Error checking locations: {e}"),
+        }
+    }
+
+    // Don't count towards resolving while the monitor keeps going up and down
+    match (
+        tracked_incid.flapping,
+        is_flapping(&monitor_id, tracked_incid.flapping).await,
+    ) {
+        (false, true) => {
+            set_flapping(&tracked_incid.incident.id, &monitor.name, Flapping::Started).await;
+            return;
+        }
+        (true, true) => return,
+        (true, false) => {
+            set_flapping(
+                &tracked_incid.incident.id,
+                &monitor.name,
+                Flapping::SettledUp,
+            )
+            .await;
+            return;
+        }
+        (false, false) => {}
+    }
+
     // This is the culprit for the missing tracked_monitor issue
     if tracked_incid.success >= 1 {
         // If the incident is already being tracked and the success count is greater than 0,
@@ -444,6 +574,9 @@ pub async fn resolve_incident(monitor_id: String) {
 
         // Sync the registry with the database
         sync_registry(incid_registry).await;
+
+        // Back to the normal schedule now that the incident is resolved
+        set_failing(&monitor_id, false).await;
     }
 }
 
@@ -458,7 +591,7 @@ pub async fn sync_registry(registry: MutexGuard<'_, Vec<TrackedIncident>>) {
     }
 
     // Get all incidents from the database and check if they are already in the database
-    let db_incidents = match query!("SELECT id FROM tracked_incidents")
+    let db_incidents = match query!("SELECT id, monitor_id FROM tracked_incidents")
         .fetch_all(&pool)
         .await
     {
@@ -480,7 +613,7 @@ pub async fn sync_registry(registry: MutexGuard<'_, Vec<TrackedIncident>>) {
     // Insert any new incidents into the database
     for incident in tracked_incidents.iter() {
         let query = query!(
-            "INSERT INTO tracked_incidents (monitor_id, id, title, started_at, acknowledged_at, resolved_at, auto_resolved, success, monitoring_created, investigating_created) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+            "INSERT INTO tracked_incidents (monitor_id, id, title, started_at, acknowledged_at, resolved_at, auto_resolved, success, monitoring_created, investigating_created, flapping) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
             incident.monitor_id.to_string(),
             incident.incident.id.to_string(),
             incident.incident.title.to_string(),
@@ -490,7 +623,8 @@ pub async fn sync_registry(registry: MutexGuard<'_, Vec<TrackedIncident>>) {
             incident.incident.auto_resolved,
             incident.success as i32,
             incident.monitoring_created as bool,
-            incident.investigating_created as bool
+            incident.investigating_created as bool,
+            incident.flapping
         )
         .execute(&pool)
         .await;
@@ -500,16 +634,17 @@ pub async fn sync_registry(registry: MutexGuard<'_, Vec<TrackedIncident>>) {
         }
     }
 
-    // Remove any incidents from the database that are no longer in the registry
+    // Remove any incidents from the database that are no longer in the registry,
+    // leaving the ones tracked by other instances alone
     for incident in db_incidents.iter() {
-        let mut found = false;
-        for tracked_incident in tracked_incidents.iter() {
-            if tracked_incident.incident.id == incident.id {
-                found = true;
-                break;
-            }
+        if !owns(&incident.monitor_id) {
+            continue;
         }
 
+        let found = registry
+            .iter()
+            .any(|tracked_incident| tracked_incident.incident.id == incident.id);
+
         if !found {
             let query = query!(
                 "DELETE FROM tracked_incidents WHERE id = $1",
@@ -582,6 +717,14 @@ pub async fn load_registry() {
         return;
     }
 
+    // Skip incidents already loaded, or tracked by another instance
+    tracked_incids.retain(|incident| {
+        owns(&incident.monitor_id)
+            && !incid_registry
+                .iter()
+                .any(|tracked| tracked.incident.id == incident.id)
+    });
+
     tracked_incids.retain(|incident| {
         let is_valid = incident_ids.contains(&incident.id);
         if !is_valid {
@@ -604,6 +747,7 @@ pub async fn load_registry() {
             success: incident.success as u32,
             monitoring_created: incident.monitoring_created,
             investigating_created: incident.investigating_created,
+            flapping: incident.flapping,
         });
     }
 }
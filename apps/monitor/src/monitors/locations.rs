@@ -0,0 +1,268 @@
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use log::{error, info};
+use monitor::generate_id;
+use sqlx::query;
+
+use crate::{
+    config::get_config,
+    cron::schedule::Schedule,
+    monitors::{get::get_monitor, health::check_health, policy::policy},
+    ping::AddressFamily,
+    POOL,
+};
+
+/// The location pings made by this instance are shown as
+pub const MAIN_LOCATION: &str = "main";
+
+/// How many intervals a location can go without pinging a monitor before it stops counting,
+/// like a probe agent that went offline
+const STALE_INTERVALS: u32 = 3;
+
+/// Which probe locations see a monitor as down over an address family.
+#[derive(Debug, Clone, Default)]
+pub struct Consensus {
+    /// The locations whose pings fail the monitor's failure policy
+    pub down: Vec<String>,
+    /// How many locations pinged the monitor recently
+    pub locations: usize,
+    /// How many locations must see the monitor as down to open an incident
+    pub needed: usize,
+}
+
+impl Consensus {
+    /// If enough locations agree the monitor is down to open an incident.
+    pub fn reached(&self) -> bool {
+        !self.down.is_empty() && self.down.len() >= self.needed
+    }
+
+    /// If some locations see the monitor as down, but too few to open an incident.
+    pub fn degraded(&self) -> bool {
+        !self.down.is_empty() && !self.reached()
+    }
+
+    /// The locations seeing the monitor as down, for incident reports.
+    pub fn describe(&self) -> String {
+        self.down.join(", ")
+    }
+}
+
+/// The locations checking every monitor, this instance (`None`) and each probe agent location.
+pub fn locations() -> Vec<Option<String>> {
+    let config = get_config().agents;
+
+    let agents = match config.enabled {
+        true => config.locations.into_iter().map(|l| Some(l.name)).collect(),
+        false => vec![],
+    };
+
+    [vec![None], agents].concat()
+}
+
+/// Work out which locations see a monitor as down over an address family, using its failure policy.
+/// Locations without enough pings yet, like a newly added agent, count as up.
+/// Locations that haven't pinged the monitor for a few intervals don't count at all, neither as up nor down,
+/// and only recent pings are used, so an agent that went away doesn't vote with its last pings forever.
+/// Pings that failed during maintenance are left out.
+pub async fn consensus(
+    monitor_id: &str,
+    address_family: AddressFamily,
+) -> Result<Consensus, sqlx::Error> {
+    let config = get_config().incidents.auto;
+    let policy = policy(monitor_id).await;
+    let period = period(monitor_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let live_since = now - period * STALE_INTERVALS;
+    // Room for every ping the policy looks at, on top of the time a location can go quiet
+    let pings_since = live_since - period * policy.pings(config.pings_threshold).min(1000) as u32;
+
+    let mut live = 0;
+    let mut down = vec![];
+
+    for location in locations() {
+        if !pinged_since(monitor_id, address_family, location.as_deref(), live_since).await? {
+            continue;
+        }
+
+        live += 1;
+
+        if policy
+            .down(
+                monitor_id,
+                address_family,
+                location.as_deref(),
+                config.pings_threshold,
+                pings_since,
+            )
+            .await?
+        {
+            down.push(location.as_deref().unwrap_or(MAIN_LOCATION).to_string());
+        }
+    }
+
+    let needed = match config.location_quorum as usize {
+        0 => live / 2 + 1,
+        quorum => quorum.min(live),
+    };
+
+    Ok(Consensus {
+        down,
+        locations: live,
+        needed,
+    })
+}
+
+/// The time between checks of a monitor, to tell recent pings from stale ones.
+async fn period(monitor_id: &str) -> Result<Duration, sqlx::Error> {
+    let monitor = get_monitor(monitor_id).await?;
+
+    Ok(Schedule::for_monitor(&monitor)
+        .ok()
+        .and_then(|schedule| schedule.period())
+        .unwrap_or(Duration::from_secs(60)))
+}
+
+/// If a location pinged a monitor over an address family since the given time.
+async fn pinged_since(
+    monitor_id: &str,
+    address_family: AddressFamily,
+    location: Option<&str>,
+    since: NaiveDateTime,
+) -> Result<bool, sqlx::Error> {
+    let pool = POOL.clone();
+
+    let row = query!(
+        r#"SELECT EXISTS (SELECT 1 FROM pings WHERE monitor_id = $1 AND address_family IS NOT DISTINCT FROM $2 AND location IS NOT DISTINCT FROM $3 AND created_at >= $4) AS "pinged!""#,
+        monitor_id,
+        address_family.as_str(),
+        location,
+        since
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(row.pinged)
+}
+
+/// Record a regional degradation while too few locations see a monitor as down to open an incident,
+/// and resolve it once every location can reach the monitor again or it became an incident.
+pub async fn track_degradation(
+    monitor_id: &str,
+    address_family: AddressFamily,
+    consensus: &Consensus,
+) {
+    let pool = POOL.clone();
+
+    let open = match query!(
+        "SELECT id FROM degradations WHERE monitor_id = $1 AND address_family IS NOT DISTINCT FROM $2 AND resolved_at IS NULL",
+        monitor_id,
+        address_family.as_str()
+    )
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(open) => open,
+        Err(e) => {
+            error!("Error fetching degradations: {e}");
+            return;
+        }
+    };
+
+    let result = match (open, consensus.degraded()) {
+        (Some(open), true) => query!(
+            "UPDATE degradations SET locations = $1 WHERE id = $2",
+            &consensus.down,
+            open.id
+        )
+        .execute(&pool)
+        .await
+        .map(|_| ()),
+        (None, true) => {
+            info!(
+                "Monitor {monitor_id} is down from {}, recording a regional degradation",
+                consensus.describe()
+            );
+
+            query!(
+                "INSERT INTO degradations (id, monitor_id, address_family, locations) VALUES ($1, $2, $3, $4)",
+                generate_id(),
+                monitor_id,
+                address_family.as_str(),
+                &consensus.down
+            )
+            .execute(&pool)
+            .await
+            .map(|_| ())
+        }
+        (Some(open), false) => {
+            info!("Regional degradation of monitor {monitor_id} is over");
+
+            query!(
+                "UPDATE degradations SET resolved_at = $1 WHERE id = $2",
+                chrono::Utc::now().naive_utc(),
+                open.id
+            )
+            .execute(&pool)
+            .await
+            .map(|_| ())
+        }
+        (None, false) => Ok(()),
+    };
+
+    if let Err(e) = result {
+        error!("Error tracking degradation: {e}");
+    }
+}
+
+/// Update the degradations of a monitor after a successful ping.
+/// With a single location there's nothing to track, failures go straight to incidents.
+pub async fn recovered(monitor_id: &str, address_family: AddressFamily) {
+    if locations().len() < 2 {
+        return;
+    }
+
+    match consensus(monitor_id, address_family).await {
+        Ok(consensus) => track_degradation(monitor_id, address_family, &consensus).await,
+        Err(e) => error!("Error checking locations: {e}"),
+    }
+}
+
+/// Check a monitor's health after a probe agent reported a ping for it.
+/// Incidents are opened by [`check_health`] as usual, and still resolved by this instance's own checks.
+pub async fn evaluate(monitor_id: &str) {
+    if !get_config().incidents.auto.enabled {
+        return;
+    }
+
+    let monitor = match get_monitor(monitor_id).await {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            error!("Error fetching monitor: {e}");
+            return;
+        }
+    };
+
+    for options in monitor.options.expand() {
+        let consensus = match consensus(&monitor.id, options.address_family).await {
+            Ok(consensus) => consensus,
+            Err(e) => {
+                error!("Error checking locations: {e}");
+                continue;
+            }
+        };
+
+        match consensus.down.is_empty() {
+            true => track_degradation(&monitor.id, options.address_family, &consensus).await,
+            false => {
+                check_health(
+                    monitor.id.to_string(),
+                    monitor.url.to_string(),
+                    options.address_family,
+                )
+                .await
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use log::error;
+use sqlx::query;
+
+use crate::POOL;
+
+/// If an event linked to the monitor is in progress.
+/// Monitors under maintenance don't open incidents or send notifications,
+/// and their failed pings don't count towards `pings_threshold`.
+pub async fn under_maintenance(monitor_id: &str) -> bool {
+    let pool = POOL.clone();
+
+    match query!(
+        "SELECT EXISTS (SELECT 1 FROM monitors_to_events JOIN events ON events.id = monitors_to_events.event_id WHERE monitors_to_events.monitor_id = $1 AND events.status = 'in_progress') AS \"active!\"",
+        monitor_id
+    )
+    .fetch_one(&pool)
+    .await
+    {
+        Ok(row) => row.active,
+        Err(e) => {
+            error!("Error checking maintenance of monitor {monitor_id}: {e}");
+            false
+        }
+    }
+}
@@ -1,6 +1,19 @@
+pub mod adaptive;
+pub mod content;
 pub mod create;
+pub mod dependencies;
+pub mod flapping;
+pub mod get;
 pub mod health;
+pub mod locations;
+pub mod maintenance;
+pub mod pause;
+pub mod pings;
+pub mod policy;
+pub mod remove;
 pub mod update;
 
 pub use create::create_job;
+pub use pause::{pause_job, resume_job, set_paused};
+pub use remove::remove_job;
 pub use update::update_job;
@@ -0,0 +1,82 @@
+use log::info;
+use sqlx::query;
+use tokio::sync::MutexGuard;
+use tokio_cron_scheduler::JobScheduler;
+
+use crate::{
+    cron::worker::MonitorJobMetadata,
+    monitors::{create::forget_running, create_job, get::get_monitor},
+    POOL,
+};
+
+/// Store if a monitor is paused, so it stays that way after a restart or a change of leader.
+/// Done by the instance receiving the request, the one leading the monitor then only updates its jobs.
+pub async fn set_paused(monitor_id: &str, paused: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let pool = POOL.clone();
+
+    let updated = query!(
+        "UPDATE monitors SET paused = $1 WHERE id = $2",
+        paused,
+        monitor_id
+    )
+    .execute(&pool)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err("Monitor not found".into());
+    }
+
+    Ok(())
+}
+
+/// Remove the jobs of a paused monitor, see [`set_paused`].
+pub async fn pause_job(
+    monitor_id: String,
+    sched: MutexGuard<'_, JobScheduler>,
+    mut registry: MutexGuard<'_, Vec<MonitorJobMetadata>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Pausing monitor {monitor_id}");
+
+    let ids = registry
+        .iter()
+        .filter(|jmd| jmd.monitor_id == monitor_id)
+        .map(|jmd| jmd.id)
+        .collect::<Vec<_>>();
+
+    for id in ids {
+        if let Err(e) = sched.remove(&id).await {
+            return Err(e.to_string().into());
+        }
+
+        info!("Removed job {id}");
+    }
+
+    registry.retain(|job| job.monitor_id != monitor_id);
+    forget_running(&monitor_id);
+
+    Ok(())
+}
+
+/// Create the job of a resumed monitor again, see [`set_paused`].
+pub async fn resume_job(
+    monitor_id: String,
+    sched: MutexGuard<'_, JobScheduler>,
+    registry: MutexGuard<'_, Vec<MonitorJobMetadata>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Resuming monitor {monitor_id}");
+
+    // The monitor wasn't paused, don't create a second job
+    if registry.iter().any(|jmd| jmd.monitor_id == monitor_id) {
+        return Ok(());
+    }
+
+    // It was paused again before the forwarded resume got here
+    let monitor = get_monitor(&monitor_id).await?;
+    if monitor.paused {
+        return Ok(());
+    }
+
+    create_job(monitor, sched, registry).await?;
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use monitor::generate_id;
+use serde::{Deserialize, Serialize};
+use sqlx::query;
+
+use crate::{
+    ping::{http::HttpPingResponse, tcp::TcpPingResponse, PingOptions, PingTimings},
+    POOL,
+};
+
+/// A ping ready to be stored in the `pings` table, or reported by a probe agent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PingRecord {
+    pub monitor_id: String,
+    pub r#type: String,
+    pub success: bool,
+    pub status: Option<i32>,
+    pub latency: i32,
+    pub headers: Option<serde_json::Value>,
+    pub body: Option<String>,
+    pub address_family: Option<String>,
+    pub ip: Option<String>,
+    pub timings: PingTimings,
+    /// A hash of the normalized body, if the monitor checks for content changes
+    pub content_hash: Option<String>,
+    /// The failed attempts made before this ping, if the check was retried
+    pub retries: Option<serde_json::Value>,
+    /// How long the check waited for a free slot before running
+    pub queue_latency: Option<i32>,
+    /// The probe agent location that made the ping, `None` for this instance
+    pub location: Option<String>,
+    /// If the ping failed while the monitor was under maintenance
+    #[serde(default)]
+    pub maintenance: bool,
+}
+
+/// A failed attempt of a check that was retried, kept on the final ping for debugging.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PingAttempt {
+    pub status: Option<i32>,
+    pub latency: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PingRecord {
+    pub fn from_http(monitor_id: &str, options: &PingOptions, result: HttpPingResponse) -> Self {
+        PingRecord {
+            monitor_id: monitor_id.to_string(),
+            r#type: "http".to_string(),
+            success: result.success,
+            status: Some(result.status),
+            latency: result.latency,
+            headers: Some(serde_json::to_value(result.headers).unwrap_or(serde_json::Value::Null)),
+            body: result.body,
+            address_family: options.address_family.as_str().map(|f| f.to_string()),
+            ip: result.ip,
+            timings: result.timings,
+            content_hash: None,
+            retries: None,
+            queue_latency: None,
+            location: None,
+            maintenance: false,
+        }
+    }
+
+    pub fn from_tcp(monitor_id: &str, options: &PingOptions, result: TcpPingResponse) -> Self {
+        PingRecord {
+            monitor_id: monitor_id.to_string(),
+            r#type: "tcp".to_string(),
+            success: result.success,
+            status: None,
+            latency: result.latency,
+            headers: None,
+            body: None,
+            address_family: options.address_family.as_str().map(|f| f.to_string()),
+            ip: result.ip,
+            timings: result.timings,
+            content_hash: None,
+            retries: None,
+            queue_latency: None,
+            location: None,
+            maintenance: false,
+        }
+    }
+
+    /// Insert the ping into the database.
+    pub async fn insert(&self) -> Result<(), sqlx::Error> {
+        let pool = POOL.clone();
+
+        query!(
+            "INSERT INTO pings (id, monitor_id, type, success, status, latency, headers, body, address_family, ip, dns_latency, connect_latency, tls_latency, ttfb_latency, download_latency, content_hash, retries, queue_latency, location, maintenance) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)",
+            generate_id(),
+            self.monitor_id,
+            self.r#type,
+            self.success,
+            self.status,
+            self.latency,
+            self.headers,
+            self.body,
+            self.address_family,
+            self.ip,
+            self.timings.dns,
+            self.timings.connect,
+            self.timings.tls,
+            self.timings.ttfb,
+            self.timings.download,
+            self.content_hash,
+            self.retries,
+            self.queue_latency,
+            self.location,
+            self.maintenance
+        )
+        .execute(&pool)
+        .await
+        .map(|_| ())
+    }
+}
+
+/// Record that a scheduled check was skipped because the previous one was still running.
+pub async fn insert_skipped(monitor_id: &str) -> Result<(), sqlx::Error> {
+    let pool = POOL.clone();
+
+    query!(
+        "INSERT INTO skipped_checks (id, monitor_id) VALUES ($1, $2)",
+        generate_id(),
+        monitor_id
+    )
+    .execute(&pool)
+    .await
+    .map(|_| ())
+}
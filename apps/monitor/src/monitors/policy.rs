@@ -0,0 +1,269 @@
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use log::error;
+use serde::Deserialize;
+use sqlx::query;
+
+use crate::{config::get_config, ping::AddressFamily, POOL};
+
+/// The most pings a `k/n` policy can look at
+pub const MAX_CHECKS: u32 = 100;
+
+/// The longest window a ratio policy can look at, in minutes
+pub const MAX_MINUTES: u32 = 1440;
+
+/// When a location sees a monitor as down, parsed from strings like `consecutive`, `3/5` or `50%/10m`.
+/// Set for the instance in `incidents.auto` and overridden per monitor in the `failure_policy` column.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum FailurePolicy {
+    /// The last `pings_threshold` pings all failed
+    #[default]
+    Consecutive,
+    /// At least `failures` of the last `checks` pings failed, like `3/5`
+    KOfN { failures: u32, checks: u32 },
+    /// More than `percent` of the pings in the last `minutes` failed, like `50%/10m`
+    Ratio { percent: u32, minutes: u32 },
+}
+
+impl FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        let policy = policy.trim();
+
+        if policy.eq_ignore_ascii_case("consecutive") {
+            return Ok(FailurePolicy::Consecutive);
+        }
+
+        let (left, right) = match policy.split_once('/') {
+            Some(parts) => parts,
+            None => return Err(format!("Invalid failure policy {policy}")),
+        };
+
+        match left.trim().strip_suffix('%') {
+            Some(percent) => {
+                let percent = match percent.trim().parse::<u32>() {
+                    Ok(percent) if percent < 100 => percent,
+                    _ => return Err(format!("Invalid failure ratio {left}")),
+                };
+
+                let minutes = match right
+                    .trim()
+                    .strip_suffix('m')
+                    .map(|m| m.trim().parse::<u32>())
+                {
+                    Some(Ok(minutes)) if minutes > 0 && minutes <= MAX_MINUTES => minutes,
+                    _ => return Err(format!("Invalid failure window {right}")),
+                };
+
+                Ok(FailurePolicy::Ratio { percent, minutes })
+            }
+            None => {
+                let failures = match left.trim().parse::<u32>() {
+                    Ok(failures) if failures > 0 => failures,
+                    _ => return Err(format!("Invalid failure count {left}")),
+                };
+
+                let checks = match right.trim().parse::<u32>() {
+                    Ok(checks) if checks >= failures && checks <= MAX_CHECKS => checks,
+                    _ => return Err(format!("Invalid check count {right}")),
+                };
+
+                Ok(FailurePolicy::KOfN { failures, checks })
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for FailurePolicy {
+    type Error = String;
+
+    fn try_from(policy: String) -> Result<Self, Self::Error> {
+        policy.parse()
+    }
+}
+
+impl FailurePolicy {
+    /// How many of the last pings the policy looks at, a ratio looks at a time window instead.
+    pub fn pings(&self, pings_threshold: u64) -> u64 {
+        match *self {
+            FailurePolicy::Consecutive => pings_threshold,
+            FailurePolicy::KOfN { checks, .. } => checks as u64,
+            FailurePolicy::Ratio { .. } => 0,
+        }
+    }
+
+    /// If the pings a location made over an address family fail the policy.
+    /// Locations without enough pings yet count as up, a ratio needs at least `pings_threshold` pings in its window.
+    /// Pings older than `since` are too old to tell anything and left out, a ratio uses its own window.
+    /// Pings that failed during maintenance are left out.
+    pub async fn down(
+        &self,
+        monitor_id: &str,
+        address_family: AddressFamily,
+        location: Option<&str>,
+        pings_threshold: u64,
+        since: NaiveDateTime,
+    ) -> Result<bool, sqlx::Error> {
+        let pool = POOL.clone();
+
+        let limit = match *self {
+            FailurePolicy::Ratio { percent, minutes } => {
+                let since = Utc::now().naive_utc() - Duration::minutes(minutes as i64);
+
+                let pings = query!(
+                    "SELECT success FROM pings WHERE monitor_id = $1 AND address_family IS NOT DISTINCT FROM $2 AND location IS NOT DISTINCT FROM $3 AND maintenance is false AND created_at >= $4",
+                    monitor_id,
+                    address_family.as_str(),
+                    location,
+                    since
+                )
+                .fetch_all(&pool)
+                .await?;
+
+                let failed = pings.iter().filter(|p| !p.success).count();
+
+                return Ok(pings.len() >= pings_threshold as usize
+                    && failed * 100 > percent as usize * pings.len());
+            }
+            _ => self.pings(pings_threshold),
+        };
+
+        let pings = query!(
+            "SELECT success FROM pings WHERE monitor_id = $1 AND address_family IS NOT DISTINCT FROM $3 AND location IS NOT DISTINCT FROM $4 AND maintenance is false AND created_at >= $5 ORDER BY created_at DESC LIMIT $2",
+            monitor_id,
+            limit as i64,
+            address_family.as_str(),
+            location,
+            since
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        if pings.len() < limit as usize {
+            return Ok(false);
+        }
+
+        let failed = pings.iter().filter(|p| !p.success).count();
+
+        Ok(match *self {
+            FailurePolicy::KOfN { failures, .. } => failed >= failures as usize,
+            _ => failed == pings.len(),
+        })
+    }
+}
+
+/// The failure policy of a monitor, or the instance's one if it doesn't set its own.
+pub async fn policy(monitor_id: &str) -> FailurePolicy {
+    let pool = POOL.clone();
+    let default = get_config().incidents.auto.failure_policy;
+
+    let policy = match query!(
+        "SELECT failure_policy FROM monitors WHERE id = $1",
+        monitor_id
+    )
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(Some(row)) => row.failure_policy,
+        Ok(None) => None,
+        Err(e) => {
+            error!("Error fetching failure policy of monitor {monitor_id}: {e}");
+            None
+        }
+    };
+
+    match policy.map(|policy| policy.parse::<FailurePolicy>()) {
+        Some(Ok(policy)) => policy,
+        Some(Err(e)) => {
+            error!("{e} for monitor {monitor_id}, using the default policy");
+            default
+        }
+        None => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(policy: &str) -> Result<FailurePolicy, String> {
+        policy.parse()
+    }
+
+    #[test]
+    fn parses_policies() {
+        assert_eq!(parse("consecutive"), Ok(FailurePolicy::Consecutive));
+        assert_eq!(parse(" Consecutive "), Ok(FailurePolicy::Consecutive));
+        assert_eq!(
+            parse("3/5"),
+            Ok(FailurePolicy::KOfN {
+                failures: 3,
+                checks: 5
+            })
+        );
+        assert_eq!(
+            parse(" 5 / 5 "),
+            Ok(FailurePolicy::KOfN {
+                failures: 5,
+                checks: 5
+            })
+        );
+        assert_eq!(
+            parse("50%/10m"),
+            Ok(FailurePolicy::Ratio {
+                percent: 50,
+                minutes: 10
+            })
+        );
+        assert_eq!(
+            parse("0 % / 1 m"),
+            Ok(FailurePolicy::Ratio {
+                percent: 0,
+                minutes: 1
+            })
+        );
+    }
+
+    #[test]
+    fn parses_the_limits() {
+        assert!(parse(&format!("1/{MAX_CHECKS}")).is_ok());
+        assert!(parse(&format!("99%/{MAX_MINUTES}m")).is_ok());
+
+        assert!(parse(&format!("1/{}", MAX_CHECKS + 1)).is_err());
+        assert!(parse(&format!("50%/{}m", MAX_MINUTES + 1)).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_policies() {
+        for invalid in [
+            "", "always", "5/3", "0/5", "-1/5", "3/", "/5", "100%/10m", "0%/0m", "50%/10", "50%/m",
+            "50/10m", "3/5/7",
+        ] {
+            assert!(parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn ratios_look_at_a_window_instead_of_pings() {
+        assert_eq!(FailurePolicy::Consecutive.pings(3), 3);
+        assert_eq!(
+            FailurePolicy::KOfN {
+                failures: 2,
+                checks: 5
+            }
+            .pings(3),
+            5
+        );
+        assert_eq!(
+            FailurePolicy::Ratio {
+                percent: 50,
+                minutes: 10
+            }
+            .pings(3),
+            0
+        );
+    }
+}
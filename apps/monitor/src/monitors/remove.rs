@@ -0,0 +1,32 @@
+use log::info;
+use tokio::sync::MutexGuard;
+use tokio_cron_scheduler::JobScheduler;
+
+use crate::{cron::worker::MonitorJobMetadata, monitors::create::forget_running};
+
+/// Remove the jobs of a monitor, returning how many were removed.
+pub async fn remove_job(
+    monitor_id: &str,
+    sched: MutexGuard<'_, JobScheduler>,
+    mut registry: MutexGuard<'_, Vec<MonitorJobMetadata>>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let ids = registry
+        .iter()
+        .filter(|jmd| jmd.monitor_id == monitor_id)
+        .map(|jmd| jmd.id)
+        .collect::<Vec<_>>();
+
+    for id in ids.iter() {
+        if let Err(e) = sched.remove(id).await {
+            return Err(e.to_string().into());
+        }
+
+        registry.retain(|job| job.id != *id);
+
+        info!("Removed job {id}");
+    }
+
+    forget_running(monitor_id);
+
+    Ok(ids.len())
+}
@@ -1,9 +1,11 @@
 use log::info;
-use sqlx::query;
 use tokio::sync::MutexGuard;
 use tokio_cron_scheduler::JobScheduler;
 
-use crate::{monitors::create_job, MON_REGISTRY, POOL, SCHED};
+use crate::{
+    monitors::{create_job, get::get_monitor},
+    MON_REGISTRY, SCHED,
+};
 
 use crate::cron::worker::MonitorJobMetadata;
 
@@ -14,26 +16,19 @@ pub async fn update_job(
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Updating job with monitor id {monitor_id}");
 
-    let pool = POOL.clone();
-
-    let monitor = match query!("SELECT * FROM monitors WHERE id = $1", monitor_id)
-        .fetch_one(&pool)
-        .await
-    {
+    let monitor = match get_monitor(&monitor_id).await {
         Ok(monitor) => monitor,
         Err(_) => return Err("Monitor not found".into()),
     };
 
+    // Paused monitors don't have a job, it gets created when the monitor is resumed
+    if monitor.paused {
+        info!("Monitor {monitor_id} is paused, not creating a job");
+        return Ok(());
+    }
+
     // create new job
-    create_job(
-        monitor_id.clone(),
-        monitor.url,
-        monitor.r#type,
-        monitor.interval.to_string(),
-        sched,
-        registry,
-    )
-    .await?;
+    create_job(monitor, sched, registry).await?;
 
     let sched = match SCHED.get() {
         Some(sched) => sched,
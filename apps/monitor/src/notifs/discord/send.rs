@@ -50,10 +50,25 @@ pub async fn send_discord(
             let formatted_content = format!("## Some monitors are down! \n\nAffected monitors: \n{}\n\nStarted: <t:{}:R>\nCurrently: {}\n\n[[View Incident]({})]", monitors, incident.started_at, "Investigating", url);
             formatted_content
         } // TemplateOptions::AutoIncidentMonitoring => "auto-incid-recovering".to_string(),
-          // TemplateOptions::AutoIncidnetFailing => "auto-incid-failing".to_string(),
-          // TemplateOptions::AutoIncidnetResolve => "auto-incid-resolve".to_string(),
+        // TemplateOptions::AutoIncidnetFailing => "auto-incid-failing".to_string(),
+        // TemplateOptions::AutoIncidnetResolve => "auto-incid-resolve".to_string(),
+        TemplateOptions::ContentChange => {
+            return Err("Content changes aren't incident notifications".to_string())
+        }
     };
 
+    post_discord(urls, content).await
+}
+
+/// Send a plain message to all Discord channels of a workspace.
+pub async fn send_discord_message(workspace: Workspace, content: String) -> Result<(), String> {
+    let urls = get_discord_urls(workspace.id).await?;
+
+    post_discord(urls, content).await
+}
+
+/// Post a message to each Discord webhook URL.
+async fn post_discord(urls: Vec<String>, content: String) -> Result<(), String> {
     let client = reqwest::Client::new();
 
     let body = serde_json::json!({
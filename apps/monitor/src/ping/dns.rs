@@ -0,0 +1,82 @@
+use std::net::{IpAddr, SocketAddr};
+
+use hickory_resolver::{
+    config::{LookupIpStrategy, NameServerConfigGroup, ResolverConfig},
+    name_server::TokioConnectionProvider,
+    TokioResolver,
+};
+use tokio::net::lookup_host;
+
+use super::{AddressFamily, PingOptions};
+
+/// Parse the `resolve` override of a monitor into the IP address to connect to.
+pub fn parse_resolve(resolve: &str) -> Result<IpAddr, String> {
+    resolve
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .map_err(|_| format!("Invalid resolve address: {resolve}"))
+}
+
+/// Build a resolver that only queries the given DNS server (`1.1.1.1` or `1.1.1.1:53`).
+fn resolver(server: &str, address_family: AddressFamily) -> Result<TokioResolver, String> {
+    let (ip, port) = match server.parse::<SocketAddr>() {
+        Ok(addr) => (addr.ip(), addr.port()),
+        Err(_) => (parse_resolve(server)?, 53),
+    };
+
+    let config = ResolverConfig::from_parts(
+        None,
+        vec![],
+        NameServerConfigGroup::from_ips_clear(&[ip], port, true),
+    );
+
+    let mut builder =
+        TokioResolver::builder_with_config(config, TokioConnectionProvider::default());
+
+    builder.options_mut().ip_strategy = match address_family {
+        AddressFamily::Ipv4 => LookupIpStrategy::Ipv4Only,
+        AddressFamily::Ipv6 => LookupIpStrategy::Ipv6Only,
+        AddressFamily::Auto | AddressFamily::Dual => LookupIpStrategy::Ipv4AndIpv6,
+    };
+
+    Ok(builder.build())
+}
+
+/// Resolve `host:port` to the addresses a probe should try, honouring the monitor's
+/// resolve override, custom DNS server and address family.
+pub async fn lookup(
+    host: &str,
+    port: u16,
+    options: &PingOptions,
+) -> Result<Vec<SocketAddr>, String> {
+    let addrs = match (&options.resolve, &options.dns_server) {
+        (Some(resolve), _) => vec![SocketAddr::new(parse_resolve(resolve)?, port)],
+        (None, Some(server)) => resolver(server, options.address_family)?
+            .lookup_ip(host)
+            .await
+            .map_err(|e| format!("DNS lookup for {host} failed: {e}"))?
+            .into_iter()
+            .map(|ip| SocketAddr::new(ip, port))
+            .collect(),
+        (None, None) => lookup_host((host, port))
+            .await
+            .map_err(|e| format!("{e}"))?
+            .collect(),
+    };
+
+    let addrs = addrs
+        .into_iter()
+        .filter(|addr| options.address_family.matches(addr))
+        .collect::<Vec<_>>();
+
+    if addrs.is_empty() {
+        return Err(match options.address_family.as_str() {
+            Some(family) => format!("No {family} address found for {host}"),
+            None => format!("No address found for {host}"),
+        });
+    }
+
+    Ok(addrs)
+}
@@ -1,7 +1,59 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use http_body_util::{BodyExt, Empty, Limited};
+use hyper::{
+    body::{Bytes, Incoming},
+    client::conn::{http1, http2},
+    header::{
+        ACCEPT, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, HOST, LOCATION, USER_AGENT,
+    },
+    Request, Response,
+};
+use hyper_util::rt::{TokioExecutor, TokioIo};
 use log::error;
+use once_cell::sync::Lazy;
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    time::timeout,
+};
+use tokio_rustls::{
+    rustls::{crypto::ring, pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+
+use super::{dns, proxy, PingOptions, PingTimings};
+
+static CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The longest a request may take once connected, monitors checked more often get their interval
+static REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+static MAX_REDIRECTS: usize = 10;
+/// The largest body read, before and after decompressing it
+static MAX_BODY: usize = 10 * 1024 * 1024;
+static USER_AGENT_VALUE: &str = concat!("Miru/", env!("CARGO_PKG_VERSION"));
+
+static TLS_CONFIG: Lazy<Arc<ClientConfig>> = Lazy::new(|| {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+    };
+
+    let mut config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Arc::new(config)
+});
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HttpPingResponse {
@@ -10,6 +62,10 @@ pub struct HttpPingResponse {
     pub body: Option<String>,
     pub latency: i32,
     pub headers: std::collections::HashMap<String, String>,
+    /// The IP address that was connected to
+    pub ip: Option<String>,
+    /// How long each phase of the request took
+    pub timings: PingTimings,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,56 +74,341 @@ pub struct HttpPingErrorResponse {
     pub error: String,
 }
 
-pub async fn http_ping(url: String) -> Result<HttpPingResponse, HttpPingErrorResponse> {
-    let now = chrono::Utc::now();
-    let client = match reqwest::Client::builder()
-        .connect_timeout(Duration::from_secs(30))
-        .build()
+/// The parts of a response we keep.
+struct Fetched {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+    ip: Option<String>,
+}
+
+/// An open connection to send the request over, HTTP/2 if the server picked it during the TLS handshake.
+enum Sender {
+    Http1(http1::SendRequest<Empty<Bytes>>),
+    Http2(http2::SendRequest<Empty<Bytes>>),
+}
+
+impl Sender {
+    async fn handshake<S>(stream: S, http2: bool) -> Result<Sender, String>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     {
-        Ok(client) => client,
-        Err(err) => {
-            error!("Failed to create reqwest client: {err:?}");
-            return Err(HttpPingErrorResponse {
-                response: HttpPingResponse {
-                    status: 503,
-                    success: false,
-                    latency: (chrono::Utc::now() - now)
-                        .num_milliseconds()
-                        .try_into()
-                        .unwrap_or(i32::MAX),
-                    headers: std::collections::HashMap::new(),
-                    body: None,
-                },
-                error: format!("{err}"),
+        let io = TokioIo::new(stream);
+
+        if http2 {
+            let (sender, conn) = http2::handshake(TokioExecutor::new(), io)
+                .await
+                .map_err(|e| format!("HTTP/2 handshake failed: {e}"))?;
+
+            tokio::spawn(async move {
+                if let Err(e) = conn.await {
+                    error!("HTTP connection error: {e}");
+                }
             });
+
+            return Ok(Sender::Http2(sender));
         }
+
+        let (sender, conn) = http1::handshake(io)
+            .await
+            .map_err(|e| format!("HTTP handshake failed: {e}"))?;
+
+        tokio::spawn(async move {
+            if let Err(e) = conn.await {
+                error!("HTTP connection error: {e}");
+            }
+        });
+
+        Ok(Sender::Http1(sender))
+    }
+
+    /// Send a GET request for the URL. HTTP/2 takes the host from the URL itself, HTTP/1 from the `Host` header.
+    async fn get(&mut self, url: &Url) -> Result<Response<Incoming>, String> {
+        let request = match self {
+            Sender::Http1(_) => {
+                let host = match url.port() {
+                    Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+                    None => url.host_str().unwrap_or_default().to_string(),
+                };
+
+                let path = match url.query() {
+                    Some(query) => format!("{}?{query}", url.path()),
+                    None => url.path().to_string(),
+                };
+
+                Request::get(path).header(HOST, host)
+            }
+            Sender::Http2(_) => {
+                let mut url = url.clone();
+                url.set_fragment(None);
+                Request::get(url.as_str())
+            }
+        };
+
+        let request = request
+            .header(ACCEPT, "*/*")
+            .header(ACCEPT_ENCODING, "gzip, deflate, br")
+            .header(USER_AGENT, USER_AGENT_VALUE)
+            .body(Empty::<Bytes>::new())
+            .map_err(|e| format!("Failed to build request: {e}"))?;
+
+        let response = match self {
+            Sender::Http1(sender) => sender.send_request(request).await,
+            Sender::Http2(sender) => sender.send_request(request).await,
+        };
+
+        response.map_err(|e| format!("Request failed: {e}"))
+    }
+}
+
+/// Open a TCP connection to the host of the URL, directly or through a proxy.
+async fn connect(
+    url: &Url,
+    options: &PingOptions,
+    timings: &mut PingTimings,
+) -> Result<TcpStream, String> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("{url} has no host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| format!("{url} has no port"))?;
+
+    if let Some(proxy) = proxy::resolve(options.proxy.as_deref(), &host)? {
+        // DNS happens on the proxy, so the whole tunnel setup counts as connecting
+        let target = match &options.resolve {
+            Some(resolve) => dns::parse_resolve(resolve)?.to_string(),
+            None => host,
+        };
+
+        let start = Instant::now();
+        let stream = timeout(CONNECT_TIMEOUT, proxy::connect(&proxy, &target, port))
+            .await
+            .map_err(|_| "Timed out connecting through proxy".to_string())??;
+        PingTimings::record(&mut timings.connect, start);
+
+        return Ok(stream);
+    }
+
+    let start = Instant::now();
+    let addrs = timeout(CONNECT_TIMEOUT, dns::lookup(&host, port, options))
+        .await
+        .map_err(|_| format!("Timed out resolving {host}"))??;
+    PingTimings::record(&mut timings.dns, start);
+
+    let start = Instant::now();
+    let stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(addrs.as_slice()))
+        .await
+        .map_err(|_| format!("Timed out connecting to {host}"))?
+        .map_err(|e| format!("Failed to connect to {host}: {e}"))?;
+    PingTimings::record(&mut timings.connect, start);
+
+    Ok(stream)
+}
+
+/// Decompress a body sent with a `Content-Encoding`, so content checks compare the page itself.
+/// Returns `None` for encodings the body is passed on in as is.
+fn decode(encoding: &str, body: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let mut reader: Box<dyn Read + '_> = match encoding.trim().to_ascii_lowercase().as_str() {
+        "gzip" | "x-gzip" => Box::new(flate2::read::GzDecoder::new(body)),
+        "deflate" => Box::new(flate2::read::ZlibDecoder::new(body)),
+        "br" => Box::new(brotli_decompressor::Decompressor::new(body, 4096)),
+        _ => return Ok(None),
+    };
+
+    let mut decoded = vec![];
+    reader
+        .by_ref()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| format!("Failed to decode {encoding} body: {e}"))?;
+
+    if decoded.len() > MAX_BODY {
+        return Err(format!("Decoded body is larger than {MAX_BODY} bytes"));
+    }
+
+    Ok(Some(decoded))
+}
+
+/// Send a GET request over an open connection and read the response.
+/// The whole exchange shares one deadline, so a server that stalls after accepting the connection can't hold the check.
+async fn send<S>(
+    stream: S,
+    url: &Url,
+    http2: bool,
+    options: &PingOptions,
+    timings: &mut PingTimings,
+) -> Result<(u16, HashMap<String, String>, Vec<u8>), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let deadline = options
+        .timeout
+        .map_or(REQUEST_TIMEOUT, |t| t.min(REQUEST_TIMEOUT));
+
+    let exchange = async {
+        let mut sender = Sender::handshake(stream, http2).await?;
+
+        let start = Instant::now();
+        let response = sender.get(url).await?;
+        PingTimings::record(&mut timings.ttfb, start);
+
+        let status = response.status().as_u16();
+        let mut headers = response
+            .headers()
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.to_string(),
+                    v.to_str()
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|_| "Invalid Header Value".to_string()),
+                )
+            })
+            .collect::<HashMap<String, String>>();
+
+        let start = Instant::now();
+        let body = Limited::new(response.into_body(), MAX_BODY)
+            .collect()
+            .await
+            .map_err(|e| format!("Failed to read body: {e}"))?
+            .to_bytes();
+        PingTimings::record(&mut timings.download, start);
+
+        // Like a browser, the body and its headers are of the decoded page
+        let decoded = match headers.get(CONTENT_ENCODING.as_str()) {
+            Some(encoding) => decode(encoding, &body)?,
+            None => None,
+        };
+
+        let body = match decoded {
+            Some(decoded) => {
+                headers.remove(CONTENT_ENCODING.as_str());
+                headers.remove(CONTENT_LENGTH.as_str());
+                decoded
+            }
+            None => body.to_vec(),
+        };
+
+        Ok((status, headers, body))
     };
 
+    timeout(deadline, exchange).await.map_err(|_| {
+        format!(
+            "Timed out after {}s waiting for a response",
+            deadline.as_secs_f32()
+        )
+    })?
+}
+
+/// Make a single request without following redirects.
+async fn request(
+    url: &Url,
+    options: &PingOptions,
+    timings: &mut PingTimings,
+) -> Result<Fetched, String> {
+    let stream = connect(url, options, timings).await?;
+    let ip = stream.peer_addr().ok().map(|addr| addr.ip().to_string());
+
+    let (status, headers, body) = match url.scheme() {
+        "https" => {
+            let host = url.host_str().unwrap_or_default();
+            let server_name = ServerName::try_from(
+                host.trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string(),
+            )
+            .map_err(|e| format!("Invalid server name {host}: {e}"))?;
+
+            // A failed handshake still took time, so it's recorded before checking the result
+            let start = Instant::now();
+            let stream = timeout(
+                CONNECT_TIMEOUT,
+                TlsConnector::from(TLS_CONFIG.clone()).connect(server_name, stream),
+            )
+            .await;
+            PingTimings::record(&mut timings.tls, start);
+
+            let stream = stream
+                .map_err(|_| format!("Timed out during TLS handshake with {host}"))?
+                .map_err(|e| format!("TLS handshake with {host} failed: {e}"))?;
+
+            let http2 = stream.get_ref().1.alpn_protocol() == Some(b"h2");
+            send(stream, url, http2, options, timings).await?
+        }
+        _ => send(stream, url, false, options, timings).await?,
+    };
+
+    Ok(Fetched {
+        status,
+        headers,
+        body,
+        ip,
+    })
+}
+
+/// Request a URL, following redirects like a browser would.
+async fn fetch(
+    url: &str,
+    options: &PingOptions,
+    timings: &mut PingTimings,
+) -> Result<Fetched, String> {
+    let mut url = Url::parse(url).map_err(|e| format!("Invalid URL {url}: {e}"))?;
+    let origin = url.host_str().map(|host| host.to_string());
+
+    // The resolve override is for the monitor's own host, other hosts it redirects to are resolved as usual
+    let redirected = PingOptions {
+        resolve: None,
+        ..options.clone()
+    };
+
+    for _ in 0..=MAX_REDIRECTS {
+        let options = match url.host_str() == origin.as_deref() {
+            true => options,
+            false => &redirected,
+        };
+
+        let fetched = request(&url, options, timings).await?;
+
+        let location = match fetched.status {
+            301 | 302 | 303 | 307 | 308 => fetched.headers.get(LOCATION.as_str()),
+            _ => None,
+        };
+
+        match location {
+            Some(location) => {
+                url = url
+                    .join(location)
+                    .map_err(|e| format!("Invalid redirect location {location}: {e}"))?;
+            }
+            None => return Ok(fetched),
+        }
+    }
+
+    Err(format!("Too many redirects (more than {MAX_REDIRECTS})"))
+}
+
+pub async fn http_ping(
+    url: String,
+    options: &PingOptions,
+) -> Result<HttpPingResponse, HttpPingErrorResponse> {
+    let now = chrono::Utc::now();
+
     let url = url
         .trim_start_matches("http://")
         .trim_start_matches("https://")
         .to_string();
 
     let url = format!("https://{url}");
-    let resp = client.get(&url).send().await;
+    let mut timings = PingTimings::default();
+    let resp = fetch(&url, options, &mut timings).await;
 
     match resp {
         Ok(resp) => {
-            let status = resp.status().as_u16() as i32;
-            let headers = resp
-                .headers()
-                .iter()
-                .map(|(k, v)| {
-                    (
-                        k.to_string(),
-                        v.to_str()
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| "Invalid Header Value".to_string()),
-                    )
-                })
-                .collect::<std::collections::HashMap<String, String>>();
-
-            let body = (resp.text().await).ok();
+            let status = resp.status as i32;
 
             Ok(HttpPingResponse {
                 status,
@@ -76,48 +417,41 @@ pub async fn http_ping(url: String) -> Result<HttpPingResponse, HttpPingErrorRes
                     .num_milliseconds()
                     .try_into()
                     .unwrap_or(i32::MAX),
-                body,
-                headers,
+                body: Some(String::from_utf8_lossy(&resp.body).to_string()),
+                headers: resp.headers,
+                ip: resp.ip,
+                timings,
             })
         }
         Err(err) => {
-            error!("[HTTPS] Failed to ping {url}: {err:?}");
+            error!("[HTTPS] Failed to ping {url}: {err}");
             let url = url.replace("https://", "http://");
-            let resp = client.get(&url).send().await;
+            // The timings are of the attempt that answered, the latency counts both attempts
+            let mut timings = PingTimings::default();
+            let resp = fetch(&url, options, &mut timings).await;
             match resp {
                 Ok(resp) => {
-                    let status = resp.status();
-                    let headers = resp
-                        .headers()
-                        .iter()
-                        .map(|(k, v)| {
-                            (
-                                k.to_string(),
-                                v.to_str()
-                                    .map(|s| s.to_string())
-                                    .unwrap_or_else(|_| "Invalid Header Value".to_string()),
-                            )
-                        })
-                        .collect::<std::collections::HashMap<String, String>>();
-                    let body = (resp.text().await).ok();
+                    let status = resp.status as i32;
 
                     Ok(HttpPingResponse {
-                        status: status.as_u16() as i32,
+                        status,
                         success: status == 200,
                         latency: (chrono::Utc::now() - now)
                             .num_milliseconds()
                             .try_into()
                             .unwrap_or(i32::MAX),
-                        body,
-                        headers,
+                        body: Some(String::from_utf8_lossy(&resp.body).to_string()),
+                        headers: resp.headers,
+                        ip: resp.ip,
+                        timings,
                     })
                 }
                 Err(err) => {
-                    error!("[HTTP] Failed to ping {url}: {err:?}");
+                    error!("[HTTP] Failed to ping {url}: {err}");
                     Err(HttpPingErrorResponse {
                         response: HttpPingResponse {
-                            // Set default status code to 503 if it's not available
-                            status: err.status().map_or(503, |s| s.as_u16() as i32),
+                            // No response means there's no status code, default to 503
+                            status: 503,
                             success: false,
                             latency: (chrono::Utc::now() - now)
                                 .num_milliseconds()
@@ -125,11 +459,234 @@ pub async fn http_ping(url: String) -> Result<HttpPingResponse, HttpPingErrorRes
                                 .unwrap_or(i32::MAX),
                             headers: std::collections::HashMap::new(),
                             body: None,
+                            ip: None,
+                            timings,
                         },
-                        error: format!("{err}"),
+                        error: err,
                     })
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        time::sleep,
+    };
+
+    use super::*;
+
+    /// Serve a plain HTTP server on `ip`, answering each request with the response `route` gives for its path.
+    /// TLS handshakes are held for a bit and then dropped, like a server without TLS would.
+    async fn serve<F>(ip: &str, route: F) -> u16
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind((ip, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let route = Arc::new(route);
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let route = route.clone();
+
+                tokio::spawn(async move {
+                    let mut buf = vec![0; 4096];
+                    let n = stream.read(&mut buf).await.unwrap_or(0);
+
+                    if buf.first() == Some(&0x16) {
+                        sleep(Duration::from_millis(200)).await;
+                        return;
+                    }
+
+                    let head = String::from_utf8_lossy(&buf[..n]);
+                    let path = head.split_whitespace().nth(1).unwrap_or("/");
+                    let _ = stream.write_all(route(path).as_bytes()).await;
+                });
+            }
+        });
+
+        port
+    }
+
+    fn response(status: &str, location: Option<&str>, body: &str) -> String {
+        let location = location
+            .map(|location| format!("Location: {location}\r\n"))
+            .unwrap_or_default();
+
+        format!(
+            "HTTP/1.1 {status}\r\n{location}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    fn resolve_to(ip: &str) -> PingOptions {
+        PingOptions {
+            resolve: Some(ip.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_redirects() {
+        let port = serve("127.0.0.1", |path| match path {
+            "/" => response("301 Moved Permanently", Some("/moved"), ""),
+            "/moved" => response("302 Found", Some("/final"), ""),
+            _ => response("200 OK", None, "arrived"),
+        })
+        .await;
+
+        let mut timings = PingTimings::default();
+        let fetched = fetch(
+            &format!("http://127.0.0.1:{port}/"),
+            &PingOptions::default(),
+            &mut timings,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fetched.status, 200);
+        assert_eq!(fetched.body, b"arrived");
+        assert_eq!(fetched.ip.as_deref(), Some("127.0.0.1"));
+    }
+
+    #[tokio::test]
+    async fn stops_after_too_many_redirects() {
+        let port = serve("127.0.0.1", |_| response("302 Found", Some("/"), "")).await;
+
+        let result = fetch(
+            &format!("http://127.0.0.1:{port}/"),
+            &PingOptions::default(),
+            &mut PingTimings::default(),
+        )
+        .await;
+
+        assert!(result.is_err_and(|e| e.contains("Too many redirects")));
+    }
+
+    #[tokio::test]
+    async fn resolve_applies_to_the_monitor_host() {
+        let port = serve("127.0.0.1", |path| match path {
+            "/" => response("302 Found", Some("/final"), ""),
+            _ => response("200 OK", None, "resolved"),
+        })
+        .await;
+
+        // `.invalid` never resolves, so reaching the server means the override was used on both requests
+        let fetched = fetch(
+            &format!("http://monitor.invalid:{port}/"),
+            &resolve_to("127.0.0.1"),
+            &mut PingTimings::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fetched.body, b"resolved");
+    }
+
+    #[tokio::test]
+    async fn resolve_does_not_apply_to_redirected_hosts() {
+        let other = serve("127.0.0.2", |_| response("200 OK", None, "other host")).await;
+        let port = serve("127.0.0.1", move |_| {
+            response("302 Found", Some(&format!("http://127.0.0.2:{other}/")), "")
+        })
+        .await;
+
+        // Nothing listens on 127.0.0.1 with the other port, so the override must not follow the redirect
+        let fetched = fetch(
+            &format!("http://monitor.invalid:{port}/"),
+            &resolve_to("127.0.0.1"),
+            &mut PingTimings::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fetched.body, b"other host");
+        assert_eq!(fetched.ip.as_deref(), Some("127.0.0.2"));
+    }
+
+    #[tokio::test]
+    async fn resets_timings_when_falling_back() {
+        let port = serve("127.0.0.1", |_| response("200 OK", None, "plain")).await;
+
+        let resp = http_ping(format!("127.0.0.1:{port}"), &PingOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(resp.body.as_deref(), Some("plain"));
+        // Only the plain HTTP attempt is timed, the latency still counts the
+        // 200ms the server held the HTTPS handshake for
+        assert!(resp.timings.tls.is_none());
+        assert!(resp.latency >= 150);
+    }
+
+    #[tokio::test]
+    async fn times_out_when_the_server_stalls() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.read(&mut [0; 4096]).await;
+            sleep(Duration::from_secs(5)).await;
+        });
+
+        let options = PingOptions {
+            timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        };
+        let result = fetch(
+            &format!("http://127.0.0.1:{port}/"),
+            &options,
+            &mut PingTimings::default(),
+        )
+        .await;
+
+        assert!(result.is_err_and(|e| e.contains("Timed out")));
+    }
+
+    #[tokio::test]
+    async fn decodes_compressed_bodies() {
+        let mut gzip = GzEncoder::new(vec![], Compression::default());
+        gzip.write_all(b"compressed").unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.read(&mut [0; 4096]).await;
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                gzip.len()
+            );
+            let _ = stream.write_all(head.as_bytes()).await;
+            let _ = stream.write_all(&gzip).await;
+        });
+
+        let fetched = fetch(
+            &format!("http://127.0.0.1:{port}/"),
+            &PingOptions::default(),
+            &mut PingTimings::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fetched.body, b"compressed");
+        assert!(!fetched.headers.contains_key("content-encoding"));
+    }
+
+    #[test]
+    fn passes_unknown_encodings_through() {
+        assert_eq!(decode("zstd", b"raw"), Ok(None));
+        assert!(decode("gzip", b"not gzip").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::Url;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_socks::tcp::Socks5Stream;
+
+use crate::config::get_config;
+
+/// A proxy that a probe should be routed through.
+#[derive(Debug, Clone)]
+pub struct Proxy {
+    pub url: Url,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Proxy {
+    fn parse(
+        url: &str,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Result<Proxy, String> {
+        let url = Url::parse(url).map_err(|e| format!("Invalid proxy URL {url}: {e}"))?;
+
+        // Credentials in the config take priority over the ones embedded in the URL
+        let username = username
+            .filter(|u| !u.is_empty())
+            .or_else(|| Some(url.username().to_string()).filter(|u| !u.is_empty()));
+        let password = password
+            .filter(|p| !p.is_empty())
+            .or_else(|| url.password().map(|p| p.to_string()));
+
+        Ok(Proxy {
+            url,
+            username,
+            password,
+        })
+    }
+}
+
+/// Figure out which proxy (if any) should be used to reach `host`.
+/// A proxy set on the monitor always wins, even for hosts in the `no_proxy` list since it was
+/// chosen for that monitor. Otherwise the instance proxy is used unless the host matches `no_proxy`.
+pub fn resolve(monitor_proxy: Option<&str>, host: &str) -> Result<Option<Proxy>, String> {
+    if let Some(url) = monitor_proxy.filter(|url| !url.is_empty()) {
+        return Proxy::parse(url, None, None).map(Some);
+    }
+
+    let config = get_config().proxy;
+
+    if !config.enabled || config.url.is_empty() || is_bypassed(host, &config.no_proxy) {
+        return Ok(None);
+    }
+
+    Proxy::parse(&config.url, config.username, config.password).map(Some)
+}
+
+/// Check if a host matches any entry of the `no_proxy` list.
+/// Entries match the host itself and any of its subdomains, `*` matches everything.
+fn is_bypassed(host: &str, no_proxy: &[String]) -> bool {
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_lowercase();
+
+    no_proxy.iter().any(|entry| {
+        let entry = entry.trim().trim_start_matches('.').to_lowercase();
+        entry == "*" || host == entry || host.ends_with(&format!(".{entry}"))
+    })
+}
+
+/// Split a `host:port` target, handling bracketed IPv6 addresses.
+pub fn split_host_port(target: &str) -> Result<(String, u16), String> {
+    let (host, port) = target
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Missing port in {target}"))?;
+
+    let port = port
+        .parse::<u16>()
+        .map_err(|_| format!("Invalid port in {target}"))?;
+
+    Ok((
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string(),
+        port,
+    ))
+}
+
+/// Open a TCP connection to `host:port` through the proxy.
+pub async fn connect(proxy: &Proxy, host: &str, port: u16) -> Result<TcpStream, String> {
+    let proxy_host = proxy
+        .url
+        .host_str()
+        .ok_or_else(|| format!("Proxy URL {} has no host", proxy.url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+
+    match proxy.url.scheme() {
+        "socks5" | "socks5h" => {
+            let proxy_addr = (proxy_host.as_str(), proxy.url.port().unwrap_or(1080));
+            let target = (host, port);
+
+            let stream = match (&proxy.username, &proxy.password) {
+                (Some(username), password) => {
+                    Socks5Stream::connect_with_password(
+                        proxy_addr,
+                        target,
+                        username,
+                        password.as_deref().unwrap_or_default(),
+                    )
+                    .await
+                }
+                (None, _) => Socks5Stream::connect(proxy_addr, target).await,
+            };
+
+            stream
+                .map(|stream| stream.into_inner())
+                .map_err(|e| format!("SOCKS5 proxy error: {e}"))
+        }
+        "http" => {
+            let proxy_port = proxy.url.port_or_known_default().unwrap_or(80);
+            let mut stream = TcpStream::connect((proxy_host.as_str(), proxy_port))
+                .await
+                .map_err(|e| format!("Failed to connect to proxy: {e}"))?;
+
+            let authority = if host.contains(':') {
+                format!("[{host}]:{port}")
+            } else {
+                format!("{host}:{port}")
+            };
+
+            let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+            if let Some(username) = &proxy.username {
+                let credentials = STANDARD.encode(format!(
+                    "{}:{}",
+                    username,
+                    proxy.password.as_deref().unwrap_or_default()
+                ));
+                request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+            }
+            request.push_str("\r\n");
+
+            stream
+                .write_all(request.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to write to proxy: {e}"))?;
+
+            // Read the response head, the tunnel is open once we get a 2xx and the blank line
+            let mut reader = BufReader::new(&mut stream);
+            let mut status_line = String::new();
+            reader
+                .read_line(&mut status_line)
+                .await
+                .map_err(|e| format!("Failed to read from proxy: {e}"))?;
+
+            loop {
+                let mut line = String::new();
+                match reader.read_line(&mut line).await {
+                    Ok(0) => break,
+                    Ok(_) if line == "\r\n" || line == "\n" => break,
+                    Ok(_) => {}
+                    Err(e) => return Err(format!("Failed to read from proxy: {e}")),
+                }
+            }
+
+            match status_line.split_whitespace().nth(1) {
+                Some(status) if status.starts_with('2') => Ok(stream),
+                _ => Err(format!("HTTP proxy refused tunnel: {}", status_line.trim())),
+            }
+        }
+        scheme => Err(format!("Unsupported proxy scheme: {scheme}")),
+    }
+}
@@ -1,10 +1,18 @@
+use std::time::Instant;
+
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpStream;
 
+use super::{dns, proxy, PingOptions, PingTimings};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TcpPingResponse {
     pub success: bool,
     pub latency: i32,
+    /// The IP address that was connected to
+    pub ip: Option<String>,
+    /// How long resolving and connecting took
+    pub timings: PingTimings,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -13,15 +21,55 @@ pub struct TcpPingErrorResponse {
     pub response: TcpPingResponse,
 }
 
-pub async fn tcp_ping(url: String) -> Result<TcpPingResponse, TcpPingErrorResponse> {
+async fn connect(
+    url: &str,
+    options: &PingOptions,
+    timings: &mut PingTimings,
+) -> Result<TcpStream, String> {
+    let (host, port) = proxy::split_host_port(url)?;
+
+    if let Some(proxy) = proxy::resolve(options.proxy.as_deref(), &host)? {
+        // Let the proxy connect to the overridden address instead of resolving the host
+        let target = match &options.resolve {
+            Some(resolve) => dns::parse_resolve(resolve)?.to_string(),
+            None => host,
+        };
+
+        let start = Instant::now();
+        let stream = proxy::connect(&proxy, &target, port).await?;
+        PingTimings::record(&mut timings.connect, start);
+
+        return Ok(stream);
+    }
+
+    let start = Instant::now();
+    let addrs = dns::lookup(&host, port, options).await?;
+    PingTimings::record(&mut timings.dns, start);
+
+    let start = Instant::now();
+    let stream = TcpStream::connect(addrs.as_slice())
+        .await
+        .map_err(|e| format!("{e}"))?;
+    PingTimings::record(&mut timings.connect, start);
+
+    Ok(stream)
+}
+
+pub async fn tcp_ping(
+    url: String,
+    options: &PingOptions,
+) -> Result<TcpPingResponse, TcpPingErrorResponse> {
     let now = chrono::Utc::now();
-    match TcpStream::connect(&url).await {
-        Ok(_) => Ok(TcpPingResponse {
+    let mut timings = PingTimings::default();
+    match connect(&url, options, &mut timings).await {
+        Ok(stream) => Ok(TcpPingResponse {
             success: true,
             latency: (chrono::Utc::now() - now)
                 .num_milliseconds()
                 .try_into()
                 .unwrap_or(i32::MAX),
+            ip: stream.peer_addr().ok().map(|addr| addr.ip().to_string()),
+            timings,
         }),
         Err(err) => Err(TcpPingErrorResponse {
             error: format!("TCP Error: {err}"),
@@ -31,6 +79,8 @@ pub async fn tcp_ping(url: String) -> Result<TcpPingResponse, TcpPingErrorRespon
                     .num_milliseconds()
                     .try_into()
                     .unwrap_or(i32::MAX),
+                ip: None,
+                timings,
             },
         }),
     }
@@ -0,0 +1,38 @@
+use actix_web::{http::header, HttpRequest, HttpResponse};
+use serde_json::json;
+
+use crate::config::get_config;
+
+/// Find the location of the probe agent making a request from its bearer token.
+pub fn authenticate(req: &HttpRequest) -> Result<String, HttpResponse> {
+    let config = get_config().agents;
+
+    if !config.enabled {
+        return Err(HttpResponse::Forbidden().json(json!({
+            "error": "Probe agents are disabled"
+        })));
+    }
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    // Compare in constant time so tokens can't be guessed from response times
+    config
+        .locations
+        .into_iter()
+        .find(|location| {
+            !location.token.is_empty()
+                && location.token.len() == token.len()
+                && openssl::memcmp::eq(location.token.as_bytes(), token.as_bytes())
+        })
+        .map(|location| location.name)
+        .ok_or_else(|| {
+            HttpResponse::Unauthorized().json(json!({
+                "error": "Invalid agent token"
+            }))
+        })
+}
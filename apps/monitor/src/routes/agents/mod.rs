@@ -0,0 +1,6 @@
+mod auth;
+mod monitors;
+mod pings;
+
+pub use monitors::agent_monitors_service;
+pub use pings::agent_pings_service;
@@ -0,0 +1,28 @@
+use actix_web::{get, HttpRequest, HttpResponse, Responder};
+use serde_json::json;
+
+use crate::{
+    agent::monitor::AgentMonitor, monitors::get::get_monitors, routes::agents::auth::authenticate,
+};
+
+/// The monitors probe agents should check, everything that isn't paused.
+/// Only what agents need to check them is sent, see [`AgentMonitor`].
+#[get("/agents/monitors")]
+pub async fn agent_monitors_service(req: HttpRequest) -> impl Responder {
+    if let Err(res) = authenticate(&req) {
+        return res;
+    }
+
+    match get_monitors().await {
+        Ok(monitors) => HttpResponse::Ok().json(
+            monitors
+                .into_iter()
+                .filter(|monitor| !monitor.paused)
+                .map(AgentMonitor::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": format!("Failed to fetch monitors: {:?}", e)
+        })),
+    }
+}
@@ -0,0 +1,58 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use log::{error, info};
+use serde_json::json;
+
+use crate::{
+    cron::cluster::{self, Forward},
+    monitors::{locations::evaluate, maintenance::under_maintenance, pings::PingRecord},
+    routes::agents::auth::authenticate,
+    shutdown::Task,
+};
+
+/// Store a ping made by a probe agent, recorded with the agent's location.
+#[post("/agents/pings")]
+pub async fn agent_pings_service(req: HttpRequest, item: web::Json<PingRecord>) -> impl Responder {
+    let location = match authenticate(&req) {
+        Ok(location) => location,
+        Err(res) => return res,
+    };
+
+    let record = item.into_inner();
+    let maintenance = !record.success && under_maintenance(&record.monitor_id).await;
+
+    // Agents don't keep bodies or check content, but don't trust them on it
+    let record = PingRecord {
+        location: Some(location),
+        body: None,
+        content_hash: None,
+        maintenance,
+        ..record
+    };
+
+    info!(
+        "Received ping for {} from {:?}",
+        record.monitor_id, record.location
+    );
+
+    if let Err(e) = record.insert().await {
+        return HttpResponse::BadRequest().json(json!({
+            "error": format!("Failed to store ping: {:?}", e)
+        }));
+    }
+
+    // Incidents are tracked by the instance running the monitor's job
+    let monitor_id = record.monitor_id;
+    if cluster::owns(&monitor_id) {
+        // The ping is stored, the next one is evaluated after a restart
+        if let Some(task) = Task::start() {
+            tokio::spawn(async move {
+                let _task = task;
+                evaluate(&monitor_id).await
+            });
+        }
+    } else if let Err(e) = Forward::CheckHealth(monitor_id).send().await {
+        error!("Failed to forward health check: {e}");
+    }
+
+    HttpResponse::Ok().json(json!({ "message": "Ping stored" }))
+}
@@ -1,4 +1,4 @@
-use crate::ping::{http_ping, tcp_ping};
+use crate::ping::{http_ping, tcp_ping, PingOptions};
 use actix_web::{get, web, HttpResponse, Responder};
 use log::error;
 use serde_json::json;
@@ -9,7 +9,7 @@ pub async fn test_service(path: web::Path<(String, String)>) -> impl Responder {
 
     match req_type.as_str() {
         "http" => {
-            let result = http_ping(url).await;
+            let result = http_ping(url, &PingOptions::default()).await;
             match result {
                 Ok(result) => HttpResponse::Ok().json(json!({
                     "success": result.success,
@@ -17,16 +17,20 @@ pub async fn test_service(path: web::Path<(String, String)>) -> impl Responder {
                     "body": result.body,
                     "latency": result.latency,
                     "headers": result.headers,
+                    "ip": result.ip,
+                    "timings": result.timings,
                 })),
                 Err(err) => HttpResponse::InternalServerError().json(json!({ "error": err })),
             }
         }
         "tcp" => {
-            let result = tcp_ping(url.clone()).await;
+            let result = tcp_ping(url.clone(), &PingOptions::default()).await;
             match result {
                 Ok(result) => HttpResponse::Ok().json(json!({
                     "success": result.success,
                     "latency": result.latency,
+                    "ip": result.ip,
+                    "timings": result.timings,
                 })),
                 Err(err) => {
                     error!("Error pinging {url}: {err:?}");
@@ -2,12 +2,21 @@ use actix_web::{post, web, HttpResponse, Responder};
 use serde_json::json;
 use sqlx::query;
 
-use crate::{events, EVENT_REGISTRY, POOL, SCHED};
+use crate::{
+    cron::cluster::{self, Forward},
+    events,
+    routes::cron::forward::forward,
+    EVENT_REGISTRY, POOL, SCHED,
+};
 
 #[post("/cron/events/create/{event_id}")]
 pub async fn create_event_job_service(path: web::Path<String>) -> impl Responder {
     let event_id = path.into_inner();
 
+    if !cluster::owns(&event_id) {
+        return forward(Forward::CreateEvent(event_id)).await;
+    }
+
     let sched = match SCHED.get() {
         Some(sched) => sched,
         None => {
@@ -0,0 +1,70 @@
+use actix_web::{get, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    cron::stats::{last_run, JobRun},
+    events::status::EventStatus,
+    EVENT_REGISTRY, SCHED,
+};
+
+#[derive(Serialize)]
+struct EventJob {
+    id: String,
+    event_id: String,
+    transition: EventStatus,
+    created_at: DateTime<Utc>,
+    last_run: Option<JobRun>,
+    /// When the scheduler next fires the job, which waits for the exact transition time if it fires early.
+    /// Event jobs have no stagger offset or jitter.
+    next_run: Option<DateTime<Utc>>,
+}
+
+/// List the event jobs scheduled on this instance, with their last and next run.
+#[get("/cron/events")]
+pub async fn list_event_jobs_service() -> impl Responder {
+    let sched = match SCHED.get() {
+        Some(sched) => sched,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get scheduler"
+            }))
+        }
+    };
+
+    let reg = match EVENT_REGISTRY.get() {
+        Some(reg) => reg,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get event registry"
+            }))
+        }
+    };
+
+    let mut sched = sched.lock().await.clone();
+    let jobs = reg.lock().await.clone();
+
+    let mut list = vec![];
+
+    for jmd in jobs {
+        // One-shot jobs that already fired have no next run
+        let next_run = match sched.next_tick_for_job(jmd.id).await {
+            Ok(next_run) => next_run,
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }))
+            }
+        };
+
+        list.push(EventJob {
+            id: jmd.id.to_string(),
+            last_run: last_run(&jmd.id),
+            event_id: jmd.event_id,
+            transition: jmd.transition,
+            created_at: jmd.created_at.into(),
+            next_run,
+        });
+    }
+
+    HttpResponse::Ok().json(list)
+}
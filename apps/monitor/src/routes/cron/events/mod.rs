@@ -1,5 +1,9 @@
 mod create;
+mod list;
+mod remove;
 mod update;
 
 pub use create::create_event_job_service;
+pub use list::list_event_jobs_service;
+pub use remove::remove_event_job_service;
 pub use update::update_event_job_service;
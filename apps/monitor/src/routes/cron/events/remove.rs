@@ -0,0 +1,53 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    cron::cluster::{self, Forward},
+    events, EVENT_REGISTRY, SCHED,
+};
+
+#[derive(Serialize, Deserialize)]
+struct EventRemovePayload {
+    ids: Vec<String>,
+}
+
+#[post("/cron/events/remove")]
+pub async fn remove_event_job_service(item: web::Json<EventRemovePayload>) -> impl Responder {
+    let ids = &item.ids;
+
+    let sched = match SCHED.get() {
+        Some(sched) => sched,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get scheduler"
+            }))
+        }
+    };
+
+    let reg = match EVENT_REGISTRY.get() {
+        Some(reg) => reg,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get event registry"
+            }))
+        }
+    };
+
+    for event_id in ids {
+        if !cluster::owns(event_id) {
+            if let Err(e) = Forward::RemoveEvent(event_id.clone()).send().await {
+                return HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }));
+            }
+
+            continue;
+        }
+
+        // Events that don't complete automatically have no job, they're still cancelled
+        if let Err(e) = events::remove_job(event_id, sched.lock().await, reg.lock().await).await {
+            return HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }));
+        }
+    }
+
+    HttpResponse::Ok().json(json!({ "message": "Job removed" }))
+}
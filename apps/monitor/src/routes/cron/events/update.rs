@@ -1,12 +1,21 @@
 use actix_web::{post, web, HttpResponse, Responder};
 use serde_json::json;
 
-use crate::{events, EVENT_REGISTRY, SCHED};
+use crate::{
+    cron::cluster::{self, Forward},
+    events,
+    routes::cron::forward::forward,
+    EVENT_REGISTRY, SCHED,
+};
 
 #[post("/cron/events/update/{event_id}")]
 pub async fn update_event_job_service(path: web::Path<String>) -> impl Responder {
     let event_id = path.into_inner();
 
+    if !cluster::owns(&event_id) {
+        return forward(Forward::UpdateEvent(event_id)).await;
+    }
+
     let sched = match SCHED.get() {
         Some(sched) => sched,
         None => {
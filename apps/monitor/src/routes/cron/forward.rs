@@ -0,0 +1,16 @@
+use actix_web::HttpResponse;
+use serde_json::json;
+
+use crate::cron::cluster::Forward;
+
+/// Forward a job change to the instance leading the monitor or event.
+pub async fn forward(change: Forward) -> HttpResponse {
+    match change.send().await {
+        Ok(_) => HttpResponse::Accepted().json(json!({
+            "message": "Forwarded to the instance running the job"
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": format!("Failed to forward job change: {:?}", e)
+        })),
+    }
+}
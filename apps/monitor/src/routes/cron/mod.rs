@@ -1,7 +1,9 @@
 pub mod events;
+mod forward;
 pub mod monitors;
 
 pub use events::create_event_job_service;
 pub use monitors::{
-    create_monitor_job_service, remove_monitor_job_service, update_monitor_job_service,
+    create_monitor_job_service, pause_monitor_job_service, remove_monitor_job_service,
+    resume_monitor_job_service, update_monitor_job_service,
 };
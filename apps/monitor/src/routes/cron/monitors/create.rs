@@ -1,13 +1,24 @@
 use actix_web::{post, web, HttpResponse, Responder};
 use serde_json::json;
-use sqlx::query;
 
-use crate::{monitors, MON_REGISTRY, POOL, SCHED};
+use crate::{
+    cron::{
+        cluster::{self, Forward},
+        schedule::Schedule,
+    },
+    monitors::{self, get::get_monitor},
+    routes::cron::forward::forward,
+    MON_REGISTRY, SCHED,
+};
 
 #[post("/cron/monitors/create/{monitor_id}")]
 pub async fn create_monitor_job_service(path: web::Path<String>) -> impl Responder {
     let monitor_id = path.into_inner();
 
+    if !cluster::owns(&monitor_id) {
+        return forward(Forward::CreateMonitor(monitor_id)).await;
+    }
+
     let sched = match SCHED.get() {
         Some(sched) => sched,
         None => {
@@ -26,12 +37,7 @@ pub async fn create_monitor_job_service(path: web::Path<String>) -> impl Respond
         }
     };
 
-    let pool = POOL.clone();
-
-    let monitor = match query!("SELECT * FROM monitors WHERE id = $1", monitor_id)
-        .fetch_one(&pool)
-        .await
-    {
+    let monitor = match get_monitor(&monitor_id).await {
         Ok(monitor) => monitor,
         Err(_) => {
             return HttpResponse::NotFound().json(json!({
@@ -40,11 +46,16 @@ pub async fn create_monitor_job_service(path: web::Path<String>) -> impl Respond
         }
     };
 
+    if monitor.paused {
+        return HttpResponse::Ok().json(json!({ "message": "Monitor is paused" }));
+    }
+
+    if let Err(e) = Schedule::for_monitor(&monitor) {
+        return HttpResponse::BadRequest().json(json!({ "error": e }));
+    }
+
     match monitors::create_job(
-        monitor_id,
-        monitor.url,
-        monitor.r#type,
-        monitor.interval.to_string(),
+        monitor,
         sched.clone().lock().await,
         reg.clone().lock().await,
     )
@@ -0,0 +1,70 @@
+use actix_web::{get, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    cron::stats::{last_run, JobRun},
+    MON_REGISTRY, SCHED,
+};
+
+#[derive(Serialize)]
+struct MonitorJob {
+    id: String,
+    monitor_id: String,
+    cron_expr: String,
+    failing: bool,
+    created_at: DateTime<Utc>,
+    last_run: Option<JobRun>,
+    /// When the scheduler next fires the job. The check itself starts after the job's delay,
+    /// the stagger offset of `every` schedules plus the random jitter from the config.
+    next_tick: Option<DateTime<Utc>>,
+}
+
+/// List the monitor jobs scheduled on this instance, with their last run and next tick.
+#[get("/cron/monitors")]
+pub async fn list_monitor_jobs_service() -> impl Responder {
+    let sched = match SCHED.get() {
+        Some(sched) => sched,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get scheduler"
+            }))
+        }
+    };
+
+    let reg = match MON_REGISTRY.get() {
+        Some(reg) => reg,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get monitor registry"
+            }))
+        }
+    };
+
+    let mut sched = sched.lock().await.clone();
+    let jobs = reg.lock().await.clone();
+
+    let mut list = vec![];
+
+    for jmd in jobs {
+        let next_tick = match sched.next_tick_for_job(jmd.id).await {
+            Ok(next_tick) => next_tick,
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }))
+            }
+        };
+
+        list.push(MonitorJob {
+            id: jmd.id.to_string(),
+            last_run: last_run(&jmd.id),
+            monitor_id: jmd.monitor_id,
+            cron_expr: jmd.cron_expr,
+            failing: jmd.failing,
+            created_at: jmd.created_at.into(),
+            next_tick,
+        });
+    }
+
+    HttpResponse::Ok().json(list)
+}
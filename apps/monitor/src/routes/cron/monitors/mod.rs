@@ -1,7 +1,13 @@
 mod create;
+mod list;
+mod pause;
 mod remove;
+mod resume;
 mod update;
 
 pub use create::create_monitor_job_service;
+pub use list::list_monitor_jobs_service;
+pub use pause::pause_monitor_job_service;
 pub use remove::remove_monitor_job_service;
+pub use resume::resume_monitor_job_service;
 pub use update::update_monitor_job_service;
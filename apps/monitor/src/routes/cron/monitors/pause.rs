@@ -0,0 +1,60 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use serde_json::json;
+
+use crate::{
+    cron::cluster::{self, Forward},
+    monitors,
+    routes::cron::forward::forward,
+    MON_REGISTRY, SCHED,
+};
+
+#[post("/cron/monitors/pause/{monitor_id}")]
+pub async fn pause_monitor_job_service(path: web::Path<String>) -> impl Responder {
+    let monitor_id = path.into_inner();
+
+    // Stored here, so it isn't lost when no instance leads the monitor right now
+    if let Err(e) = monitors::set_paused(&monitor_id, true).await {
+        return HttpResponse::InternalServerError().json(json!({
+            "error": format!("Failed to pause monitor: {:?}", e)
+        }));
+    }
+
+    if !cluster::owns(&monitor_id) {
+        return forward(Forward::PauseMonitor(monitor_id)).await;
+    }
+
+    let sched = match SCHED.get() {
+        Some(sched) => sched,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get scheduler"
+            }))
+        }
+    };
+
+    let reg = match MON_REGISTRY.get() {
+        Some(reg) => reg,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to get monitor registry"
+            }))
+        }
+    };
+
+    match monitors::pause_job(
+        monitor_id,
+        sched.clone().lock().await,
+        reg.clone().lock().await,
+    )
+    .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(json!({
+                "error": format!("Failed to pause monitor: {:?}", e)
+            }))
+        }
+    }
+
+    HttpResponse::Ok().json(json!({ "message": "Monitor paused" }))
+}
@@ -1,9 +1,11 @@
 use actix_web::{post, web, HttpResponse, Responder};
-use log::info;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::{MON_REGISTRY, SCHED};
+use crate::{
+    cron::cluster::{self, Forward},
+    monitors, MON_REGISTRY, SCHED,
+};
 
 #[derive(Serialize, Deserialize)]
 struct MonitorRemovePayload {
@@ -33,25 +35,21 @@ pub async fn remove_monitor_job_service(item: web::Json<MonitorRemovePayload>) -
     };
 
     for monitor_id in ids {
-        let id = {
-            let reg = reg.lock().await;
-            match reg
-                .iter()
-                .find(|jmd| jmd.monitor_id == *monitor_id)
-            {
-                Some(jmd) => jmd.clone().id,
-                None => return HttpResponse::NotFound().json(json!({ "error": "Job not found" })),
+        if !cluster::owns(monitor_id) {
+            if let Err(e) = Forward::RemoveMonitor(monitor_id.clone()).send().await {
+                return HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }));
             }
-        };
 
-        let sched_lock = sched.lock().await;
-        if let Err(e) = sched_lock.remove(&id).await {
-            return HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }));
+            continue;
         }
 
-        reg.lock().await.retain(|job| job.id != id);
-
-        info!("Removed job {id}");
+        match monitors::remove_job(monitor_id, sched.lock().await, reg.lock().await).await {
+            Ok(0) => return HttpResponse::NotFound().json(json!({ "error": "Job not found" })),
+            Ok(_) => {}
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }))
+            }
+        }
     }
 
     HttpResponse::Ok().json(json!({ "message": "Job removed" }))
@@ -1,12 +1,21 @@
 use actix_web::{post, web, HttpResponse, Responder};
 use serde_json::json;
 
-use crate::{monitors, MON_REGISTRY, SCHED};
+use crate::{
+    cron::cluster::{self, Forward},
+    monitors,
+    routes::cron::forward::forward,
+    MON_REGISTRY, SCHED,
+};
 
 #[post("/cron/monitors/update/{monitor_id}")]
 pub async fn update_monitor_job_service(path: web::Path<String>) -> impl Responder {
     let monitor_id = path.into_inner();
 
+    if !cluster::owns(&monitor_id) {
+        return forward(Forward::UpdateMonitor(monitor_id)).await;
+    }
+
     let sched = match SCHED.get() {
         Some(sched) => sched,
         None => {
@@ -1,3 +1,4 @@
+pub mod agents;
 pub mod config;
 pub mod core;
 pub mod cron;
@@ -6,7 +7,7 @@ pub mod not_found;
 pub use config::config_service;
 pub use core::{hello_service, ping_service, test_service};
 pub use cron::{
-    create_event_job_service, create_monitor_job_service, remove_monitor_job_service,
-    update_monitor_job_service,
+    create_event_job_service, create_monitor_job_service, pause_monitor_job_service,
+    remove_monitor_job_service, resume_monitor_job_service, update_monitor_job_service,
 };
 pub use not_found::not_found_service;
@@ -0,0 +1,119 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use actix_web::dev::ServerHandle;
+use log::{error, info, warn};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio_cron_scheduler::JobScheduler;
+
+use crate::{config::get_config, monitors::health::sync_registry, INCID_REGISTRY, SCHED};
+
+/// Set once a shutdown signal was received
+static STOPPING: AtomicBool = AtomicBool::new(false);
+/// How many tasks are still running, see [`Task`]
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// If the process is shutting down, in which case jobs firing now shouldn't start any work.
+pub fn stopping() -> bool {
+    STOPPING.load(Ordering::Acquire)
+}
+
+/// Work the process waits for before exiting, like a check and the notifications it sends.
+/// The task is done when dropped.
+pub struct Task(());
+
+impl Task {
+    /// Start a task, or `None` if the process is shutting down.
+    pub fn start() -> Option<Task> {
+        IN_FLIGHT.fetch_add(1, Ordering::AcqRel);
+
+        if stopping() {
+            IN_FLIGHT.fetch_sub(1, Ordering::AcqRel);
+            return None;
+        }
+
+        Some(Task(()))
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Wait for SIGTERM or Ctrl-C. Never resolves if the signals can't be listened for.
+pub async fn signalled() {
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(e) => {
+            error!("Failed to listen for SIGTERM: {e}");
+            return std::future::pending().await;
+        }
+    };
+
+    tokio::select! {
+        _ = terminate.recv() => info!("Received SIGTERM, shutting down..."),
+        _ = tokio::signal::ctrl_c() => info!("Received Ctrl-C, shutting down..."),
+    }
+}
+
+/// Shut down gracefully on SIGTERM or Ctrl-C, stopping the server once jobs are drained.
+pub fn listen(server: ServerHandle) {
+    tokio::spawn(async move {
+        signalled().await;
+        shutdown().await;
+
+        info!("Stopping server...");
+        server.stop(true).await;
+    });
+}
+
+/// Drain the jobs, then store the incident registry.
+async fn shutdown() {
+    let sched = match SCHED.get() {
+        Some(sched) => Some(sched.lock().await.clone()),
+        None => None,
+    };
+
+    drain(sched).await;
+
+    match INCID_REGISTRY.get() {
+        Some(reg) => {
+            sync_registry(reg.lock().await).await;
+            info!("Stored the incident registry");
+        }
+        None => error!("Failed to get incident registry"),
+    }
+}
+
+/// Stop firing jobs and wait up to `scheduler.shutdown_timeout` for running checks and
+/// notifications to finish.
+pub async fn drain(sched: Option<JobScheduler>) {
+    STOPPING.store(true, Ordering::Release);
+
+    if let Some(mut sched) = sched {
+        if let Err(e) = sched.shutdown().await {
+            error!("Failed to stop the scheduler: {e}");
+        }
+    }
+
+    let timeout = Duration::from_secs(get_config().scheduler.shutdown_timeout);
+    let drained = tokio::time::timeout(timeout, async {
+        while IN_FLIGHT.load(Ordering::Acquire) > 0 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await;
+
+    match drained {
+        Ok(_) => info!("All running jobs finished"),
+        Err(_) => warn!(
+            "{} job(s) still running after {}s, stopping anyway",
+            IN_FLIGHT.load(Ordering::Acquire),
+            timeout.as_secs()
+        ),
+    }
+}
@@ -49,12 +49,12 @@ export const columns: ColumnDef<EventWithMonitors>[] = [
 		},
 	},
 	{
-		accessorKey: "completed",
-		header: "Completed",
+		accessorKey: "status",
+		header: "Status",
 		cell: ({ row }) => {
 			return (
-				<p>
-					{row.original.completed ? "Yes" : "No"}
+				<p className="capitalize">
+					{row.original.status.replace("_", " ")}
 				</p>
 			);
 		},
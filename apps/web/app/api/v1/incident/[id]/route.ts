@@ -24,7 +24,9 @@ export async function GET(request: Request, { params }: { params: Promise<{ id:
 	const data = await db.query.incidents.findFirst({
 		with: {
 			monitorsToIncidents: {
-				columns: {},
+				columns: {
+					impacted: true
+				},
 				with: {
 					monitor: true
 				}
@@ -59,7 +61,7 @@ export async function GET(request: Request, { params }: { params: Promise<{ id:
 		error: false,
 		incident: {
 			...incidentData,
-			monitors: monitorsToIncidents.map((monitorToIncident) => monitorToIncident.monitor)
+			monitors: monitorsToIncidents.map((monitorToIncident) => ({ ...monitorToIncident.monitor, impacted: monitorToIncident.impacted }))
 		}
 	}, {
 		status: 200,
@@ -192,7 +194,9 @@ export async function PATCH(request: Request, { params }: { params: Promise<{ id
 	const data = await db.query.incidents.findFirst({
 		with: {
 			monitorsToIncidents: {
-				columns: {},
+				columns: {
+					impacted: true
+				},
 				with: {
 					monitor: true
 				}
@@ -216,7 +220,7 @@ export async function PATCH(request: Request, { params }: { params: Promise<{ id
 		error: false,
 		incident: {
 			...incidentData,
-			monitors: monitorsToIncidents.map((monitorToIncident) => monitorToIncident.monitor)
+			monitors: monitorsToIncidents.map((monitorToIncident) => ({ ...monitorToIncident.monitor, impacted: monitorToIncident.impacted }))
 		}
 	}, {
 		status: 200,
@@ -31,7 +31,9 @@ export async function GET(request: Request) {
 	const data = await db.query.incidents.findMany({
 		with: {
 			monitorsToIncidents: {
-				columns: {},
+				columns: {
+					impacted: true
+				},
 				with: {
 					monitor: true
 				}
@@ -52,7 +54,7 @@ export async function GET(request: Request) {
 
 		return {
 			...incidentData,
-			monitors: monitorsToIncidents.map((monitorToIncident) => monitorToIncident.monitor)
+			monitors: monitorsToIncidents.map((monitorToIncident) => ({ ...monitorToIncident.monitor, impacted: monitorToIncident.impacted }))
 		};
 	});
 
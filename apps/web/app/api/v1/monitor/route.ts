@@ -112,7 +112,7 @@ export async function POST(request: Request) {
 			"Access-Control-Allow-Origin": "*",
 		}
 	}).then(async (res) => {
-		if (res.status !== 200) {
+		if (!res.ok) {
 			const json = await res.json();
 
 			if (json.error) {
@@ -25,12 +25,14 @@ export default function InstanceConfigForm({ config }: { config: MiruConfig }) {
 		const t = toast.loading("Updating configuration...");
 
 		const res = await updateConfig({
+			...config,
 			email: {
 				enabled: enableEmail,
 				verification: emailVerification,
 			},
 			incidents: {
 				auto: {
+					...config.incidents.auto,
 					enabled: autoIncidents,
 					pings_threshold: pingsThreshold,
 				},
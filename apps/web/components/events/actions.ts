@@ -36,6 +36,49 @@ export async function getAllEventsWithMonitors(workspaceId: string): Promise<Eve
 	return events;
 }
 
+/// If a number parses as a positive `u32` in the monitor service
+function isPositiveCount(value: string) {
+	return /^\+?\d+$/.test(value) && Number(value) > 0 && Number(value) <= 0xffffffff;
+}
+
+/// If a recurrence `UNTIL` is a UTC date-time like `20261231T235959Z` or a date like `20261231`
+function isValidUntil(value: string) {
+	const match = value.match(/^(\d{4})(\d{2})(\d{2})(?:T(\d{2})(\d{2})(\d{2})Z)?$/);
+	if (!match) return false;
+
+	const [year, month, day, hour, minute, second] = match.slice(1).map((part) => Number(part ?? 0));
+	const date = new Date(Date.UTC(year, month - 1, day, hour, minute, second));
+
+	return date.getUTCFullYear() === year && date.getUTCMonth() === month - 1 && date.getUTCDate() === day
+		&& date.getUTCHours() === hour && date.getUTCMinutes() === minute && date.getUTCSeconds() === second;
+}
+
+/// If a recurrence rule parses in the monitor service, see `Recurrence::from_str`
+function isValidRecurrence(rule: string) {
+	rule = rule.trim();
+	if (rule.startsWith("RRULE:")) rule = rule.slice("RRULE:".length);
+
+	const parts = new Map<string, string>();
+	for (const part of rule.split(";").filter((part) => part.length > 0)) {
+		const index = part.indexOf("=");
+		if (index === -1) return false;
+		parts.set(part.slice(0, index).toUpperCase(), part.slice(index + 1));
+	}
+
+	for (const [key, value] of parts) {
+		const valid = {
+			FREQ: () => ["DAILY", "WEEKLY", "MONTHLY"].includes(value.toUpperCase()),
+			INTERVAL: () => isPositiveCount(value),
+			COUNT: () => isPositiveCount(value),
+			UNTIL: () => isValidUntil(value),
+		}[key];
+
+		if (!valid?.()) return false;
+	}
+
+	return parts.has("FREQ") && !(parts.has("COUNT") && parts.has("UNTIL"));
+}
+
 export const createEvent = actionClient.inputSchema(z.object({
 	title: z.string(),
 	message: z.string(),
@@ -43,10 +86,12 @@ export const createEvent = actionClient.inputSchema(z.object({
 	startsAt: z.date(),
 	duration: z.number(),
 	autoComplete: z.boolean(),
+	/// RRULE-style recurrence like `FREQ=WEEKLY;COUNT=10`, null if the event happens once
+	recurrence: z.string().refine(isValidRecurrence, "Invalid recurrence rule").nullable().optional(),
 }), { handleValidationErrorsShape: async (ve) => flattenValidationErrors(ve).fieldErrors }).outputSchema(z.object({
 	error: z.boolean(),
 	message: z.string(),
-})).action(async ({ parsedInput: { title, message, monitorIds, startsAt, duration, autoComplete } }) => {
+})).action(async ({ parsedInput: { title, message, monitorIds, startsAt, duration, autoComplete, recurrence } }) => {
 	const id = generateId();
 
 	startsAt.setSeconds(0);
@@ -59,6 +104,7 @@ export const createEvent = actionClient.inputSchema(z.object({
 		startsAt,
 		duration,
 		autoComplete,
+		recurrence,
 		completed: false,
 	}).returning();
 
@@ -83,7 +129,7 @@ export const createEvent = actionClient.inputSchema(z.object({
 			"Access-Control-Allow-Origin": "*",
 		}
 	}).then(async (res) => {
-		if (res.status === 200) {
+		if (res.ok) {
 			revalidatePath("/admin/[workspaceSlug]/events", "layout");
 		} else {
 			const json = await res.json();
@@ -116,10 +162,12 @@ export const editEvent = actionClient.inputSchema(z.object({
 	startsAt: z.date().optional(),
 	duration: z.number().optional(),
 	autoComplete: z.boolean().optional(),
+	/// RRULE-style recurrence like `FREQ=WEEKLY;COUNT=10`, null to stop repeating
+	recurrence: z.string().refine(isValidRecurrence, "Invalid recurrence rule").nullable().optional(),
 }), { handleValidationErrorsShape: async (ve) => flattenValidationErrors(ve).fieldErrors }).outputSchema(z.object({
 	error: z.boolean(),
 	message: z.string(),
-})).action(async ({ parsedInput: { id, title, message, monitorIds, startsAt, duration, autoComplete } }) => {
+})).action(async ({ parsedInput: { id, title, message, monitorIds, startsAt, duration, autoComplete, recurrence } }) => {
 	const event = await db.query.events.findFirst({
 		where: eq(events.id, id),
 	});
@@ -137,7 +185,8 @@ export const editEvent = actionClient.inputSchema(z.object({
 		message,
 		startsAt,
 		duration,
-		autoComplete
+		autoComplete,
+		recurrence,
 	}).where(eq(events.id, id)).catch((err) => {
 		console.error(err);
 		return {
@@ -146,6 +195,30 @@ export const editEvent = actionClient.inputSchema(z.object({
 		}
 	});
 
+	// The event's jobs are scheduled from its start, duration and recurrence, so they're recreated when those change
+	if (startsAt !== undefined || duration !== undefined || autoComplete !== undefined || recurrence !== undefined) {
+		const res = await fetch(`${process.env.MONITOR_URL}/cron/events/update/${id}`, {
+			method: "POST",
+			headers: {
+				"Content-Type": "application/json",
+				"Access-Control-Allow-Origin": "*",
+			}
+		}).then(async (res) => {
+			if (!res.ok) {
+				const json = await res.json();
+				return { error: true, message: json.error ?? "Failed to reschedule event" };
+			}
+		}).catch((e) => {
+			console.error(e);
+			return { error: true, message: "Couldn't reach the monitor service. Is it running?" };
+		});
+
+		if (res?.error) {
+			revalidatePath(`/admin/[workspaceSlug]/events`, "layout");
+			return res;
+		}
+	}
+
 	const mte = await db.select().from(monitorsToEvents).where(eq(monitorsToEvents.eventId, id));
 
 	// Check if all the monitors are valid
@@ -191,6 +264,26 @@ export const editEvent = actionClient.inputSchema(z.object({
 })
 
 export const deleteEvents = actionClient.inputSchema(z.array(z.string().nonempty())).action(async ({ parsedInput: ids }) => {
+	// Remove the scheduled jobs first so they don't act on the events while they're deleted
+	await fetch(`${process.env.MONITOR_URL}/cron/events/remove`, {
+		method: "POST",
+		headers: {
+			"Content-Type": "application/json",
+			"Access-Control-Allow-Origin": "*",
+		},
+		body: JSON.stringify({
+			ids: ids
+		})
+	}).then(async (res) => {
+		if (!res.ok) {
+			const json = await res.json();
+			return { error: true, message: json.error ?? "Failed to stop cron job" };
+		}
+	}).catch((e) => {
+		console.error(e);
+		return { error: true, message: "Couldn't reach the monitor service. Is it running?" };
+	})
+
 	// Delete event
 	await db.delete(events).where(inArray(events.id, ids)).catch((err) => {
 		console.error(err);
@@ -207,11 +300,44 @@ export const deleteEvents = actionClient.inputSchema(z.array(z.string().nonempty
 	return { error: false, message: "Event deleted successfully" };
 })
 
+/// Call off an event that isn't over yet, the monitor service removes its jobs and marks it as cancelled
+export const cancelEvent = actionClient.inputSchema(z.object({
+	id: z.string().nonempty(),
+})).outputSchema(z.object({
+	error: z.boolean(),
+	message: z.string(),
+})).action(async ({ parsedInput: { id } }) => {
+	const res = await fetch(`${process.env.MONITOR_URL}/cron/events/remove`, {
+		method: "POST",
+		headers: {
+			"Content-Type": "application/json",
+			"Access-Control-Allow-Origin": "*",
+		},
+		body: JSON.stringify({
+			ids: [id]
+		})
+	}).then(async (res) => {
+		if (!res.ok) {
+			const json = await res.json();
+			return { error: true, message: json.error ?? "Failed to cancel event" };
+		}
+
+		return { error: false, message: "Event cancelled" };
+	}).catch((e) => {
+		console.error(e);
+		return { error: true, message: "Couldn't reach the monitor service. Is it running?" };
+	})
+
+	revalidatePath("/admin/[workspaceSlug]/events", "layout");
+	return res;
+})
+
 export const markCompleted = actionClient.inputSchema(z.object({
 	id: z.string(),
 })).action(async ({ parsedInput: { id } }) => {
 	await db.update(events).set({
-		completed: true
+		completed: true,
+		status: "completed",
 	}).where(eq(events.id, id)).catch((err) => {
 		console.error(err);
 		return { error: true, message: "Failed to mark event as completed" };
@@ -0,0 +1,37 @@
+"use client";
+
+import { toast } from "sonner";
+import { cancelEvent } from "@/components/events/actions";
+import Alert from "@/components/ui/alert";
+
+export default function CancelEvent({
+	open,
+	setOpen,
+	id,
+}: {
+	open: boolean;
+	setOpen: (open: boolean) => void;
+	id: string;
+}) {
+	return (
+		<Alert
+			title="Cancel event?"
+			description="Are you sure you want to cancel this event? It will stop affecting its monitors and won't happen again. This action cannot be undone."
+			open={open}
+			setOpen={setOpen}
+			onCancel={() => setOpen(false)}
+			onSubmit={() => {
+				cancelEvent({ id }).then((res) => {
+					if (res?.data?.error) {
+						toast.error("Something went wrong!", {
+							description: res.data.message
+						})
+					} else {
+						toast.success("Event cancelled!");
+					}
+				});
+				return;
+			}}
+		/>
+	);
+}
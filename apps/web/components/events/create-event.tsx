@@ -34,6 +34,7 @@ import { toast } from "sonner";
 import { createEvent } from "@/components/events/actions";
 import Link from "next/link";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import RecurrenceSelect from "@/components/events/recurrence-select";
 
 
 export function CreateEventButton({ monitors }: { monitors: Monitor[] }) {
@@ -63,6 +64,7 @@ export default function CreateEvent({ open, setOpen, monitors }: { open: boolean
 	const [scheduledFor, setScheduledFor] = useState<Date>(new Date());
 	const [duration, setDuration] = useState<number>(20);
 	const [autoComplete, setAutoComplete] = useState<boolean>(true);
+	const [recurrence, setRecurrence] = useState<string | null>(null);
 
 	async function onSubmit(e: React.FormEvent<HTMLFormElement>) {
 		e.preventDefault();
@@ -120,6 +122,7 @@ export default function CreateEvent({ open, setOpen, monitors }: { open: boolean
 			message,
 			monitorIds: monitorList.map((monitor) => monitor.id),
 			autoComplete,
+			recurrence,
 			duration,
 			startsAt: scheduledFor,
 		});
@@ -224,6 +227,7 @@ export default function CreateEvent({ open, setOpen, monitors }: { open: boolean
 										</SelectContent>
 									</Select>
 								</div>
+								<RecurrenceSelect value={recurrence} setValue={setRecurrence} startsAt={scheduledFor} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t rounded-b-lg bg-neutral-50/50 dark:bg-neutral-900/50 p-4">
 								<DialogClose asChild>
@@ -318,6 +322,7 @@ export default function CreateEvent({ open, setOpen, monitors }: { open: boolean
 										</SelectContent>
 									</Select>
 								</div>
+								<RecurrenceSelect value={recurrence} setValue={setRecurrence} startsAt={scheduledFor} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t bg-neutral-50/50 dark:bg-neutral-900/50 p-4">
 								<DrawerClose asChild>
@@ -1,6 +1,6 @@
 "use client";
 
-import { Check, ClipboardCopy, MoreHorizontal, Pencil, Trash } from "lucide-react";
+import { Ban, Check, ClipboardCopy, MoreHorizontal, Pencil, Trash } from "lucide-react";
 import { Button, buttonVariants } from "@/components/ui/button";
 import {
 	DropdownMenu,
@@ -19,6 +19,7 @@ import { EventWithMonitors } from "@miru/types";
 import EditEvent from "@/components/events/edit-event";
 import DeleteEvent from "@/components/events/delete-event";
 import CompleteEvent from "@/components/events/complete-event";
+import CancelEvent from "@/components/events/cancel-event";
 
 export default function EventActionsDropdown({
 	event,
@@ -32,6 +33,7 @@ export default function EventActionsDropdown({
 	const [deleteOpen, setDeleteOpen] = React.useState(false);
 	const [editOpen, setEditOpen] = React.useState(false);
 	const [completeOpen, setCompleteOpen] = React.useState(false);
+	const [cancelOpen, setCancelOpen] = React.useState(false);
 	const [allMonitors, setAllMonitors] = React.useState<
 		Monitor[]
 	>([]);
@@ -70,6 +72,11 @@ export default function EventActionsDropdown({
 					open={completeOpen}
 					setOpen={setCompleteOpen}
 				/>
+				<CancelEvent
+					id={event.id}
+					open={cancelOpen}
+					setOpen={setCancelOpen}
+				/>
 				<DropdownMenu>
 					<DropdownMenuTrigger asChild className="">
 						<Button variant="ghost" {...props}>
@@ -84,6 +91,11 @@ export default function EventActionsDropdown({
 								<Check />Mark Completed
 							</DropdownMenuItem>
 						)}
+						{(event.status === "scheduled" || event.status === "in_progress") && (
+							<DropdownMenuItem onClick={() => setCancelOpen(true)}>
+								<Ban />Cancel Event
+							</DropdownMenuItem>
+						)}
 						<DropdownMenuItem onClick={() => setEditOpen(true)} disabled={event.completed || inProgress}>
 							<Pencil /> Edit
 						</DropdownMenuItem>
@@ -35,6 +35,7 @@ import { editEvent } from "@/components/events/actions";
 import Link from "next/link";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
 import { EventWithMonitors } from "@miru/types";
+import RecurrenceSelect from "@/components/events/recurrence-select";
 
 export function EditEventButton({ event, monitors }: { event: EventWithMonitors, monitors: Monitor[] }) {
 	const [open, setOpen] = React.useState(false);
@@ -63,6 +64,7 @@ export default function EditEvent({ open, setOpen, event, monitors }: { open: bo
 	const [scheduledFor, setScheduledFor] = useState<Date>(event.startsAt);
 	const [duration, setDuration] = useState<number>(event.duration);
 	const [autoComplete, setAutoComplete] = useState<boolean>(event.autoComplete);
+	const [recurrence, setRecurrence] = useState<string | null>(event.recurrence);
 
 	async function onSubmit(e: React.FormEvent<HTMLFormElement>) {
 		e.preventDefault();
@@ -114,6 +116,7 @@ export default function EditEvent({ open, setOpen, event, monitors }: { open: bo
 			autoComplete: autoComplete ?? undefined,
 			duration: duration ?? undefined,
 			startsAt: scheduledFor ?? undefined,
+			recurrence,
 		});
 
 		if (typeof res?.validationErrors !== "undefined") {
@@ -215,6 +218,7 @@ export default function EditEvent({ open, setOpen, event, monitors }: { open: bo
 										</SelectContent>
 									</Select>
 								</div>
+								<RecurrenceSelect value={recurrence} setValue={setRecurrence} startsAt={scheduledFor} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t rounded-b-lg bg-neutral-50/50 dark:bg-neutral-900/50 p-4">
 								<DialogClose asChild>
@@ -308,6 +312,7 @@ export default function EditEvent({ open, setOpen, event, monitors }: { open: bo
 										</SelectContent>
 									</Select>
 								</div>
+								<RecurrenceSelect value={recurrence} setValue={setRecurrence} startsAt={scheduledFor} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t bg-neutral-50/50 dark:bg-neutral-900/50 p-4">
 								<DrawerClose asChild>
@@ -0,0 +1,126 @@
+"use client";
+
+import { HelpCircle } from "lucide-react";
+import Link from "next/link";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import DateTimePicker from "@/components/ui/date-time-picker";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+
+/// The parts of an RRULE-style rule like `FREQ=WEEKLY;COUNT=10`, by their upper case key
+function parseRule(rule: string | null) {
+	const parts = new Map<string, string>();
+
+	for (const part of (rule ?? "").trim().replace(/^RRULE:/, "").split(";")) {
+		const index = part.indexOf("=");
+		if (index > 0) parts.set(part.slice(0, index).toUpperCase(), part.slice(index + 1));
+	}
+
+	return parts;
+}
+
+function buildRule(parts: Map<string, string>) {
+	if (!parts.has("FREQ")) return null;
+	return [...parts].map(([key, value]) => `${key}=${value}`).join(";");
+}
+
+/// Format a date as an `UNTIL` value in UTC, like `20261231T235959Z`
+function formatUntil(date: Date) {
+	return date.toISOString().replace(/\.\d{3}/, "").replace(/[-:]/g, "");
+}
+
+/// Read an `UNTIL` value, a date without a time lasts until the end of the day like in the monitor service
+function parseUntil(value: string | undefined) {
+	const match = value?.match(/^(\d{4})(\d{2})(\d{2})(?:T(\d{2})(\d{2})(\d{2})Z)?$/);
+	if (!match) return new Date();
+
+	const [year, month, day] = match.slice(1, 4).map(Number);
+	const [hour, minute, second] = match[4] ? match.slice(4).map(Number) : [23, 59, 59];
+
+	return new Date(Date.UTC(year, month - 1, day, hour, minute, second));
+}
+
+/// Pick how often an event repeats and when it stops, editing the parts of the rule it controls and keeping any others like `INTERVAL`
+export default function RecurrenceSelect({ value, setValue, startsAt, disabled = false }: { value: string | null, setValue: (value: string | null) => void, startsAt: Date, disabled?: boolean }) {
+	const parts = parseRule(value);
+	const ends = parts.has("COUNT") ? "count" : parts.has("UNTIL") ? "until" : "never";
+
+	function update(changes: Record<string, string | null>) {
+		const next = new Map(parts);
+
+		for (const [key, change] of Object.entries(changes)) {
+			if (change === null) {
+				next.delete(key);
+			} else {
+				next.set(key, change);
+			}
+		}
+
+		setValue(buildRule(next));
+	}
+
+	// A month after the event starts, it can be moved from there
+	function defaultUntil() {
+		const until = new Date(startsAt);
+		until.setMonth(until.getMonth() + 1);
+		return formatUntil(until);
+	}
+
+	return (
+		<>
+			<div className="flex flex-col gap-2 items-start w-full">
+				<div className="flex flex-row gap-2 items-center">
+					<Label>Repeat</Label>
+					<Link href="https://miru.nordstud.io/docs/concepts/events#recurring-events" target="_blank">
+						<HelpCircle className="size-4 text-neutral-500 dark:text-neutral-400" />
+					</Link>
+				</div>
+				<Select
+					value={parts.get("FREQ")?.toUpperCase() ?? "never"}
+					onValueChange={(v) => v === "never" ? setValue(null) : update({ FREQ: v })}
+					disabled={disabled}
+				>
+					<SelectTrigger className="w-full">
+						<SelectValue placeholder="Never" />
+					</SelectTrigger>
+					<SelectContent>
+						<SelectItem value="never">Never</SelectItem>
+						<SelectItem value="DAILY">Daily</SelectItem>
+						<SelectItem value="WEEKLY">Weekly</SelectItem>
+						<SelectItem value="MONTHLY">Monthly</SelectItem>
+					</SelectContent>
+				</Select>
+			</div>
+			{parts.has("FREQ") && (
+				<div className="flex flex-col gap-2 items-start w-full">
+					<Label>Ends</Label>
+					<Select
+						value={ends}
+						onValueChange={(v) => update({
+							COUNT: v === "count" ? parts.get("COUNT") ?? "10" : null,
+							UNTIL: v === "until" ? parts.get("UNTIL") ?? defaultUntil() : null,
+						})}
+						disabled={disabled}
+					>
+						<SelectTrigger className="w-full">
+							<SelectValue placeholder="Never" />
+						</SelectTrigger>
+						<SelectContent>
+							<SelectItem value="never">Never</SelectItem>
+							<SelectItem value="count">After a number of times</SelectItem>
+							<SelectItem value="until">On a date</SelectItem>
+						</SelectContent>
+					</Select>
+					{ends === "count" && (
+						<Input type="number" min={1} value={parts.get("COUNT")} disabled={disabled} onChange={(e) => {
+							update({ COUNT: String(Math.max(1, parseInt(e.target.value) || 1)) });
+						}} />
+					)}
+					{ends === "until" && (
+						<DateTimePicker date={parseUntil(parts.get("UNTIL"))} setDate={(date) => update({ UNTIL: formatUntil(date) })} disabled={disabled} />
+					)}
+				</div>
+			)}
+		</>
+	);
+}
@@ -38,6 +38,7 @@ import React from "react";
 import Alert from "@/components/ui/alert";
 import { testUrl } from "@/components/monitors/utils";
 import { usePathname } from "next/navigation";
+import MonitorSettingsFields, { monitorSettings, settingsData } from "@/components/monitors/monitor-settings";
 
 export function CreateMonitorButton() {
 	const [open, setOpen] = React.useState(false);
@@ -67,6 +68,7 @@ export default function CreateMonitor({ open, setOpen }: { open: boolean, setOpe
 	const [url, setUrl] = useState("");
 	const [type, setType] = useState<"http" | "tcp">("http");
 	const [interval, setInterval] = useState("5");
+	const [settings, setSettings] = useState(monitorSettings());
 
 	async function onSubmit(e: React.FormEvent<HTMLFormElement>) {
 		e.preventDefault();
@@ -101,6 +103,7 @@ export default function CreateMonitor({ open, setOpen }: { open: boolean, setOpe
 			url,
 			interval: parseInt(interval),
 			workspaceSlug: pathname.split("/")[2],
+			...settingsData(settings),
 		}).then((res) => {
 			if (typeof res?.validationErrors !== "undefined") {
 				return toast.error(`Invalid ${Object.keys(res.validationErrors)[0]}`, {
@@ -252,6 +255,7 @@ export default function CreateMonitor({ open, setOpen }: { open: boolean, setOpe
 										</SelectContent>
 									</Select>
 								</div>
+								<MonitorSettingsFields value={settings} setValue={setSettings} type={type} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t rounded-b-lg bg-neutral-50/50 dark:bg-neutral-900/50 p-4">
 								<DialogClose asChild>
@@ -384,6 +388,7 @@ export default function CreateMonitor({ open, setOpen }: { open: boolean, setOpe
 										</SelectContent>
 									</Select>
 								</div>
+								<MonitorSettingsFields value={settings} setValue={setSettings} type={type} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t bg-neutral-50/50 dark:bg-neutral-900/50 p-4">
 								<span className="text-neutral-400 dark:text-neutral-600 text-sm">
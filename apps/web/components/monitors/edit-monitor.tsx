@@ -29,7 +29,9 @@ import {
 import { Button, buttonVariants } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
-import { editMonitor } from "@/components/monitors/actions";
+import { editMonitor, getParentOptions } from "@/components/monitors/actions";
+import MonitorSelection from "@/components/monitors/monitor-select";
+import MonitorSettingsFields, { monitorSettings, settingsData } from "@/components/monitors/monitor-settings";
 import { toast } from "sonner";
 import Spinner from "@/components/ui/spinner";
 import { Monitor } from "@miru/types";
@@ -87,6 +89,22 @@ export default function EditMonitor({
 	const [url, setUrl] = React.useState(monitor.url);
 	const [type, setType] = React.useState<"http" | "tcp">(monitor.type);
 	const [interval, setInterval] = React.useState(monitor.interval.toString());
+	const [settings, setSettings] = React.useState(monitorSettings(monitor));
+	const [candidates, setCandidates] = React.useState<Monitor[]>([]);
+	const [parents, setParents] = React.useState<Monitor[] | null>(null);
+
+	// Only monitors that don't already depend on this one are offered, so no cycle can be picked
+	React.useEffect(() => {
+		if (!open) return;
+
+		getParentOptions(monitor.id).then((res) => {
+			const options = res?.data;
+			if (!options) return;
+
+			setCandidates(options.candidates);
+			setParents(options.candidates.filter((m) => options.parents.includes(m.id)));
+		});
+	}, [open, monitor.id]);
 
 	function onSubmit(e: React.FormEvent<HTMLFormElement>) {
 		e.preventDefault();
@@ -99,8 +117,18 @@ export default function EditMonitor({
 				type,
 				url,
 				interval: parseInt(interval),
+				...settingsData(settings),
+				// Left out until the current parents are loaded, so they aren't cleared by accident
+				parents: parents?.map((m) => m.id),
 			}
 		}).then((res) => {
+			if (typeof res?.validationErrors !== "undefined") {
+				const [field, errors] = Object.entries(res.validationErrors.data ?? {}).find(([key]) => key !== "_errors") ?? ["monitor", undefined];
+				return toast.error(`Invalid ${field}`, {
+					description: (errors as { _errors?: string[] } | undefined)?._errors?.[0],
+				});
+			}
+
 			if (res?.data?.error) {
 				toast.error("Something went wrong!", {
 					description: res.data.message
@@ -201,6 +229,16 @@ export default function EditMonitor({
 										</SelectContent>
 									</Select>
 								</div>
+								<div className="flex flex-col gap-2 items-start w-full">
+									<Label>Depends on</Label>
+									<MonitorSelection
+										monitors={candidates}
+										value={parents ?? []}
+										setValue={(value) => setParents((prev) => typeof value === "function" ? value(prev ?? []) : value)}
+										disabled={loading || parents === null}
+									/>
+								</div>
+								<MonitorSettingsFields value={settings} setValue={setSettings} type={type} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t bg-neutral-50/50 dark:bg-neutral-900/50 rounded-b-lg p-4">
 								<DialogClose asChild>
@@ -310,6 +348,16 @@ export default function EditMonitor({
 										</SelectContent>
 									</Select>
 								</div>
+								<div className="flex flex-col gap-2 items-start w-full">
+									<Label>Depends on</Label>
+									<MonitorSelection
+										monitors={candidates}
+										value={parents ?? []}
+										setValue={(value) => setParents((prev) => typeof value === "function" ? value(prev ?? []) : value)}
+										disabled={loading || parents === null}
+									/>
+								</div>
+								<MonitorSettingsFields value={settings} setValue={setSettings} type={type} disabled={loading} />
 							</div>
 							<div className="flex flex-row items-center justify-between gap-4 border-t bg-neutral-50/50 dark:bg-neutral-900/50 p-4">
 								<span className="text-neutral-400 dark:text-neutral-600 text-sm">
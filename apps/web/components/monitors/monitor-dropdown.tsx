@@ -12,7 +12,7 @@ import {
 	DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu";
 import { toast } from "sonner";
-import { pingMonitor } from "@/components/monitors/actions";
+import { pingMonitor, setMonitorPaused } from "@/components/monitors/actions";
 import React from "react";
 import EditMonitor from "@/components/monitors/edit-monitor";
 import DeleteMonitor from "@/components/monitors/delete-monitor";
@@ -130,6 +130,31 @@ export default function MonitorActionsDropdown({
 						>
 							Ping
 						</DropdownMenuItem>
+						{RankedRoles[currentMember.role] >= RankedRoles.admin && (
+							<DropdownMenuItem
+								onClick={async () => {
+									const t = toast.loading(
+										`${monitor.paused ? "Resuming" : "Pausing"} ${monitor.name}...`
+									);
+
+									await setMonitorPaused({ id: monitor.id, paused: !monitor.paused }).then((res) => {
+										if (res?.data?.error) {
+											toast.error("Something went wrong!", {
+												description: res.data.message,
+												id: t
+											})
+										} else {
+											toast.success("Success!", {
+												description: res?.data?.message,
+												id: t
+											})
+										}
+									});
+								}}
+							>
+								{monitor.paused ? "Resume" : "Pause"}
+							</DropdownMenuItem>
+						)}
 						<DropdownMenuItem
 							onClick={() => {
 								navigator.clipboard.writeText(monitor.id);
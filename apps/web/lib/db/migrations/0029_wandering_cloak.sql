@@ -0,0 +1 @@
+ALTER TABLE "monitors" ADD COLUMN "proxy" text;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE "monitors" ADD COLUMN "address_family" text DEFAULT 'auto' NOT NULL;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "address_family" text;
\ No newline at end of file
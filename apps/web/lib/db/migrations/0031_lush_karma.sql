@@ -0,0 +1,3 @@
+ALTER TABLE "monitors" ADD COLUMN "resolve" text;--> statement-breakpoint
+ALTER TABLE "monitors" ADD COLUMN "dns_server" text;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "ip" text;
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE "pings" ADD COLUMN "dns_latency" integer;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "connect_latency" integer;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "tls_latency" integer;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "ttfb_latency" integer;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "download_latency" integer;
\ No newline at end of file
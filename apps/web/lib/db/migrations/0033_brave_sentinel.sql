@@ -0,0 +1,10 @@
+CREATE TABLE "tracked_contents" (
+	"monitor_id" varchar(16) PRIMARY KEY NOT NULL,
+	"hash" text NOT NULL,
+	"body" text NOT NULL,
+	"updated_at" timestamp DEFAULT now() NOT NULL
+);--> statement-breakpoint
+ALTER TABLE "monitors" ADD COLUMN "content_check" boolean DEFAULT false NOT NULL;--> statement-breakpoint
+ALTER TABLE "monitors" ADD COLUMN "content_ignore" text[] DEFAULT '{}' NOT NULL;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "content_hash" text;--> statement-breakpoint
+ALTER TABLE "tracked_contents" ADD CONSTRAINT "tracked_contents_monitor_id_monitors_id_fk" FOREIGN KEY ("monitor_id") REFERENCES "public"."monitors"("id") ON DELETE cascade ON UPDATE no action;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE "monitors" ADD COLUMN "interval_unit" text DEFAULT 'minutes' NOT NULL;--> statement-breakpoint
+ALTER TABLE "monitors" ADD COLUMN "cron" text;
\ No newline at end of file
@@ -0,0 +1,6 @@
+CREATE TABLE "skipped_checks" (
+	"id" varchar(16) PRIMARY KEY NOT NULL,
+	"monitor_id" varchar(16) NOT NULL,
+	"created_at" timestamp DEFAULT now() NOT NULL
+);--> statement-breakpoint
+ALTER TABLE "skipped_checks" ADD CONSTRAINT "skipped_checks_monitor_id_monitors_id_fk" FOREIGN KEY ("monitor_id") REFERENCES "public"."monitors"("id") ON DELETE cascade ON UPDATE no action;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE "monitors" ADD COLUMN "retries" integer DEFAULT 0 NOT NULL;--> statement-breakpoint
+ALTER TABLE "monitors" ADD COLUMN "retry_delay" integer DEFAULT 1000 NOT NULL;--> statement-breakpoint
+ALTER TABLE "pings" ADD COLUMN "retries" json;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE "monitors" ADD COLUMN "paused" boolean DEFAULT false NOT NULL;
\ No newline at end of file
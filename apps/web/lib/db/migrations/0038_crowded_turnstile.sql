@@ -0,0 +1 @@
+ALTER TABLE "pings" ADD COLUMN "queue_latency" integer;
\ No newline at end of file
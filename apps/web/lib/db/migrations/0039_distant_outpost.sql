@@ -0,0 +1 @@
+ALTER TABLE "pings" ADD COLUMN "location" text;
\ No newline at end of file
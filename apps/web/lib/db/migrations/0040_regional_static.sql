@@ -0,0 +1,9 @@
+CREATE TABLE "degradations" (
+	"id" varchar(16) PRIMARY KEY NOT NULL,
+	"monitor_id" varchar(16) NOT NULL,
+	"address_family" text,
+	"locations" text[] NOT NULL,
+	"started_at" timestamp DEFAULT now() NOT NULL,
+	"resolved_at" timestamp
+);--> statement-breakpoint
+ALTER TABLE "degradations" ADD CONSTRAINT "degradations_monitor_id_monitors_id_fk" FOREIGN KEY ("monitor_id") REFERENCES "public"."monitors"("id") ON DELETE cascade ON UPDATE no action;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE "events" ADD COLUMN "status" text DEFAULT 'scheduled' NOT NULL;--> statement-breakpoint
+UPDATE "events" SET "status" = 'completed' WHERE "completed" = true;--> statement-breakpoint
+UPDATE "events" SET "status" = 'in_progress' WHERE "status" = 'scheduled' AND "started_at" <= (now() at time zone 'utc');
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE "events" ADD COLUMN "recurrence" text;
\ No newline at end of file
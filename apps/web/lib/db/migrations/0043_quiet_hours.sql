@@ -0,0 +1 @@
+ALTER TABLE "pings" ADD COLUMN "maintenance" boolean DEFAULT false NOT NULL;
\ No newline at end of file
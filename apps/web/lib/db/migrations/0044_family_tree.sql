@@ -0,0 +1,8 @@
+CREATE TABLE "monitor_dependencies" (
+	"monitor_id" varchar(16) NOT NULL,
+	"parent_id" varchar(16) NOT NULL,
+	CONSTRAINT "monitor_dependencies_monitor_id_parent_id_pk" PRIMARY KEY("monitor_id","parent_id")
+);--> statement-breakpoint
+ALTER TABLE "monitors_to_incidents" ADD COLUMN "impacted" boolean DEFAULT false NOT NULL;--> statement-breakpoint
+ALTER TABLE "monitor_dependencies" ADD CONSTRAINT "monitor_dependencies_monitor_id_monitors_id_fk" FOREIGN KEY ("monitor_id") REFERENCES "public"."monitors"("id") ON DELETE cascade ON UPDATE no action;--> statement-breakpoint
+ALTER TABLE "monitor_dependencies" ADD CONSTRAINT "monitor_dependencies_parent_id_monitors_id_fk" FOREIGN KEY ("parent_id") REFERENCES "public"."monitors"("id") ON DELETE cascade ON UPDATE no action;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE "tracked_incidents" ADD COLUMN "flapping" boolean DEFAULT false NOT NULL;
\ No newline at end of file
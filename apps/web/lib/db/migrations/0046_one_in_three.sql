@@ -0,0 +1 @@
+ALTER TABLE "monitors" ADD COLUMN "failure_policy" text;
\ No newline at end of file
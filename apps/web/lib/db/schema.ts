@@ -1,5 +1,5 @@
 export { user, session, account, verification, passkey } from "./schema/auth"
-export { monitors, monitorRelations, monitorsToIncidents, monitorsToIncidentsRelations, pings, monitorsToEvents, monitorsToEventsRelations } from "./schema/monitors"
+export { monitors, monitorRelations, monitorsToIncidents, monitorsToIncidentsRelations, pings, monitorsToEvents, monitorsToEventsRelations, monitorDependencies, monitorDependenciesRelations } from "./schema/monitors"
 export { incidentRelations, incidentReports, incidentReportsRelations, incidents } from "./schema/incidents"
 export { workspaces, workspaceMembers, workspacesRelations, workspaceMembersRelations, workspaceInvites } from "./schema/workspaces"
 export { statusPageMonitors, statusPageMonitorsRelations, statusPageRelations, statusPages } from "./schema/status-pages"
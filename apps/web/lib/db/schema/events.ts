@@ -1,7 +1,7 @@
 import { monitorsToEvents } from "@/lib/db/schema/monitors";
 import { generateId } from "@/lib/utils";
 import { relations } from "drizzle-orm";
-import { boolean, integer, pgTable, timestamp, varchar } from "drizzle-orm/pg-core";
+import { boolean, integer, pgTable, text, timestamp, varchar } from "drizzle-orm/pg-core";
 
 export const events = pgTable("events", {
 	/// The unique identifier for the event
@@ -14,10 +14,14 @@ export const events = pgTable("events", {
 	startsAt: timestamp("started_at").notNull(),
 	// how long event is planned to last (in minutes)
 	duration: integer("duration").notNull().default(60),
+	// RRULE-style recurrence like "FREQ=WEEKLY;COUNT=10", null if the event happens once
+	recurrence: text("recurrence"),
 	// if the event will automatically be marked as complete
 	autoComplete: boolean("auto_complete").notNull().default(false),
 	// If the event has been completed
 	completed: boolean("completed").notNull().default(false),
+	// Where the event is in its lifecycle, kept up to date by the monitor service
+	status: text("status", { enum: ["scheduled", "in_progress", "completed", "cancelled"] }).notNull().default("scheduled"),
 	// When the event was created
 	createdAt: timestamp("created_at").notNull().defaultNow(),
 	// When the event was last updated
@@ -70,4 +70,6 @@ export const trackedIncidents = pgTable("tracked_incidents", {
 	monitoring_created: boolean("monitoring_created").notNull().default(false),
 	/// If the investigating report has been created or not
 	investigating_created: boolean("investigating_created").notNull().default(false),
+	/// If the monitor keeps going up and down, holding the incident open until it's stable
+	flapping: boolean("flapping").notNull().default(false),
 })
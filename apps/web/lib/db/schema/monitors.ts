@@ -17,8 +17,32 @@ export const monitors = pgTable("monitors", {
 	type: text("type", { enum: ["http", "tcp"] }).notNull(),
 	/// The URL to monitor
 	url: text("url").notNull(),
-	/// The interval in seconds to check the monitor
+	/// The interval to check the monitor, in `intervalUnit`
 	interval: integer("interval").notNull(),
+	/// The unit of the interval
+	intervalUnit: text("interval_unit", { enum: ["seconds", "minutes"] }).notNull().default("minutes"),
+	/// A cron expression to check the monitor on instead of the interval
+	cron: text("cron"),
+	/// How many times a failed check is retried before it counts as failed
+	retries: integer("retries").notNull().default(0),
+	/// How long to wait between retries in milliseconds
+	retryDelay: integer("retry_delay").notNull().default(1000),
+	/// If the monitor is paused, paused monitors aren't checked and don't open incidents
+	paused: boolean("paused").notNull().default(false),
+	/// A proxy URL to route checks through, overriding the instance proxy
+	proxy: text("proxy"),
+	/// The address family to check over, dual checks IPv4 and IPv6 separately
+	addressFamily: text("address_family", { enum: ["auto", "ipv4", "ipv6", "dual"] }).notNull().default("auto"),
+	/// An IP address to connect to instead of resolving the host (like curl's --resolve), dual-stack monitors only check its family
+	resolve: text("resolve"),
+	/// A DNS server to resolve the host with instead of the system resolver
+	dnsServer: text("dns_server"),
+	/// If the response body should be checked for changes
+	contentCheck: boolean("content_check").notNull().default(false),
+	/// Regexes matching dynamic parts of the body to ignore when checking for changes
+	contentIgnore: text("content_ignore").array().notNull().default([]),
+	/// When the monitor counts as down, like `3/5` or `50%/10m`, null uses the instance's policy
+	failurePolicy: text("failure_policy"),
 	/// When the monitor was created
 	createdAt: timestamp("created_at").notNull().defaultNow(),
 	/// When the monitor was last updated
@@ -32,13 +56,17 @@ export const monitorRelations = relations(monitors, ({ many, one }) => ({
 		fields: [monitors.workspaceId],
 		references: [workspaces.id]
 	}),
-	statusPageMonitors: many(statusPageMonitors)
+	statusPageMonitors: many(statusPageMonitors),
+	parents: many(monitorDependencies, { relationName: "parents" }),
+	children: many(monitorDependencies, { relationName: "children" })
 }))
 
 // Join table for the many to many relationship between monitors and incidents
 export const monitorsToIncidents = pgTable("monitors_to_incidents", {
 	monitorId: varchar("monitor_id", { length: 16 }).notNull().references(() => monitors.id, { onDelete: "cascade" }),
 	incidentId: varchar("incident_id", { length: 16 }).notNull().references(() => incidents.id, { onDelete: "cascade" }),
+	/// If the monitor failed because a monitor it depends on is down, rather than being the cause of the incident
+	impacted: boolean("impacted").notNull().default(false),
 }, (t) => [
 	primaryKey({ columns: [t.monitorId, t.incidentId] })
 ])
@@ -75,6 +103,28 @@ export const monitorsToEventsRelations = relations(monitorsToEvents, ({ one }) =
 	})
 }));
 
+// Join table for the monitors a monitor depends on, a child's failures are attached to its parent's incident
+export const monitorDependencies = pgTable("monitor_dependencies", {
+	monitorId: varchar("monitor_id", { length: 16 }).notNull().references(() => monitors.id, { onDelete: "cascade" }),
+	parentId: varchar("parent_id", { length: 16 }).notNull().references(() => monitors.id, { onDelete: "cascade" }),
+}, (t) => [
+	primaryKey({ columns: [t.monitorId, t.parentId] })
+]);
+
+// Many to many relationship between monitors and the monitors they depend on
+export const monitorDependenciesRelations = relations(monitorDependencies, ({ one }) => ({
+	monitor: one(monitors, {
+		fields: [monitorDependencies.monitorId],
+		references: [monitors.id],
+		relationName: "parents"
+	}),
+	parent: one(monitors, {
+		fields: [monitorDependencies.parentId],
+		references: [monitors.id],
+		relationName: "children"
+	})
+}));
+
 export const pings = pgTable("pings", {
 	/// The unique identifier for the ping
 	id: varchar("id", { length: 16 }).primaryKey().$defaultFn(generateId),
@@ -92,6 +142,68 @@ export const pings = pgTable("pings", {
 	headers: json("headers"),
 	/// The body of the response
 	body: text("body"),
+	/// The address family the ping was made over, if the monitor is pinned to one
+	addressFamily: text("address_family", { enum: ["ipv4", "ipv6"] }),
+	/// The IP address the ping connected to
+	ip: text("ip"),
+	/// How long the DNS lookup took
+	dnsLatency: integer("dns_latency"),
+	/// How long the TCP connection took to open
+	connectLatency: integer("connect_latency"),
+	/// How long the TLS handshake took
+	tlsLatency: integer("tls_latency"),
+	/// How long it took to receive the first byte of the response
+	ttfbLatency: integer("ttfb_latency"),
+	/// How long the response body took to download
+	downloadLatency: integer("download_latency"),
+	/// A hash of the normalized response body, if the monitor checks for content changes
+	contentHash: text("content_hash"),
+	/// The failed attempts made before this ping, if the check was retried
+	retries: json("retries"),
+	/// How long the check waited for a free slot before running
+	queueLatency: integer("queue_latency"),
+	/// The probe agent location that made the ping, null for the main instance
+	location: text("location"),
+	/// If the ping failed while an event linked to the monitor was in progress, it doesn't count towards incidents
+	maintenance: boolean("maintenance").notNull().default(false),
 	/// When the ping was created
 	createdAt: timestamp("created_at").notNull().defaultNow(),
+})
+
+/// The last response body seen for each monitor checking for content changes
+export const trackedContents = pgTable("tracked_contents", {
+	/// A reference to the monitor this content belongs to
+	monitorId: varchar("monitor_id", { length: 16 }).primaryKey().references(() => monitors.id, { onDelete: "cascade" }),
+	/// A hash of the normalized response body
+	hash: text("hash").notNull(),
+	/// The normalized response body, used to show what changed
+	body: text("body").notNull(),
+	/// When the content was last changed
+	updatedAt: timestamp("updated_at").notNull().defaultNow(),
+})
+
+/// Monitor specific tables --- DO NOT EDIT OR REMOVE
+export const skippedChecks = pgTable("skipped_checks", {
+	/// The unique identifier for the skipped check
+	id: varchar("id", { length: 16 }).primaryKey().$defaultFn(generateId),
+	/// A reference to the monitor whose check was skipped
+	monitorId: varchar("monitor_id", { length: 16 }).notNull().references(() => monitors.id, { onDelete: "cascade" }),
+	/// When the check was skipped
+	createdAt: timestamp("created_at").notNull().defaultNow(),
+})
+
+/// Monitor specific tables --- DO NOT EDIT OR REMOVE
+export const degradations = pgTable("degradations", {
+	/// The unique identifier for the degradation
+	id: varchar("id", { length: 16 }).primaryKey().$defaultFn(generateId),
+	/// A reference to the monitor that is degraded
+	monitorId: varchar("monitor_id", { length: 16 }).notNull().references(() => monitors.id, { onDelete: "cascade" }),
+	/// The address family the monitor is degraded over, if it's pinned to one
+	addressFamily: text("address_family", { enum: ["ipv4", "ipv6"] }),
+	/// The probe locations seeing the monitor as down
+	locations: text("locations").array().notNull(),
+	/// When the degradation started
+	startedAt: timestamp("started_at").notNull().defaultNow(),
+	/// When every location could reach the monitor again, or the degradation became an incident
+	resolvedAt: timestamp("resolved_at"),
 })
\ No newline at end of file
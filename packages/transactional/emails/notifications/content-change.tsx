@@ -0,0 +1,122 @@
+import {
+	Body,
+	Button,
+	Container,
+	Head,
+	Heading,
+	Hr,
+	Html,
+	Link,
+	Preview,
+	Section,
+	Text,
+} from "@react-email/components";
+
+interface ContentChangeProps {
+	monitorName: string;
+	changeDiff: string;
+	viewUrl: string;
+}
+
+const ContentChange = ({ monitorName = "monitorName", changeDiff = "changeDiff", viewUrl = "viewUrl" }: ContentChangeProps) => (
+	<Html>
+		<Head />
+		<Preview>{monitorName} has changed</Preview>
+		<Body style={main}>
+			<Container style={container}>
+				<Heading style={heading}>{monitorName} has changed</Heading>
+				<Text style={paragraph}>
+					The monitoring service has detected that the content of {monitorName} has changed
+					since the last check. Here's what changed:
+				</Text>
+				<pre style={diff}>{changeDiff}</pre>
+				<Section style={buttonContainer}>
+					<Button style={button} href={viewUrl}>
+						View Monitor
+					</Button>
+				</Section>
+				<Hr style={hr} />
+				<Text style={reportLink}>
+					見る • Made by <Link href="https://nordstud.io">Nord Studio</Link>
+				</Text>
+			</Container>
+		</Body>
+	</Html>
+);
+
+ContentChange.PreviewProps = {
+	monitorName: "Website",
+	changeDiff: "-<h1>Welcome</h1>\n+<h1>Welcome back</h1>",
+	viewUrl: "https://miru.nordstud.io",
+}
+
+export default ContentChange;
+
+const main = {
+	backgroundColor: "#ffffff",
+	fontFamily:
+		'-apple-system,BlinkMacSystemFont,"Segoe UI",Roboto,Oxygen-Sans,Ubuntu,Cantarell,"Helvetica Neue",sans-serif',
+};
+
+const container = {
+	margin: "0 auto",
+	padding: "20px 0 48px",
+	maxWidth: "560px",
+	textAlign: "center" as const
+};
+
+const heading = {
+	fontSize: "24px",
+	letterSpacing: "-0.5px",
+	lineHeight: "1.3",
+	fontWeight: "400",
+	color: "#484848",
+	padding: "17px 0 0",
+};
+
+const paragraph = {
+	margin: "0 0 15px",
+	fontSize: "15px",
+	lineHeight: "1.4",
+	color: "#3c4149",
+};
+
+const diff = {
+	margin: "0 0 15px",
+	padding: "12px",
+	fontSize: "13px",
+	lineHeight: "1.4",
+	color: "#3c4149",
+	backgroundColor: "#f4f4f5",
+	borderRadius: "8px",
+	textAlign: "left" as const,
+	whiteSpace: "pre-wrap" as const,
+	overflowX: "auto" as const,
+};
+
+const buttonContainer = {
+	padding: "8px 0px 0px",
+};
+
+const button = {
+	backgroundColor: "#121212",
+	borderRadius: "8px",
+	fontWeight: "600",
+	color: "#fff",
+	fontSize: "15px",
+	textDecoration: "none",
+	display: "block",
+	padding: "12px 24px",
+	margin: "0 auto",
+	width: "140px"
+};
+
+const reportLink = {
+	fontSize: "14px",
+	color: "#b4becc",
+};
+
+const hr = {
+	borderColor: "#dfe1e4",
+	margin: "42px 0 26px",
+};
\ No newline at end of file
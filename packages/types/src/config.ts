@@ -1,4 +1,22 @@
 export type MiruConfig = {
+	agents?: {
+		/// Accept pings from probe agents running in other locations
+		enabled: boolean;
+		locations: {
+			/// The name pings from this agent are recorded with, e.g. `eu-west`
+			name: string;
+			/// The token the agent authenticates with
+			token: string;
+		}[];
+	},
+	cluster?: {
+		/// Elect a leader through Postgres advisory locks, so only one instance runs each check
+		enabled: boolean;
+		/// The number of shards monitors are split into, each led by one instance
+		shards: number;
+		/// How often in seconds to try to take over free shards, the leader's connection is checked every second
+		heartbeat: number;
+	},
 	email: {
 		enabled: boolean;
 		verification: boolean;
@@ -7,8 +25,43 @@ export type MiruConfig = {
 		auto: {
 			enabled: boolean;
 			pings_threshold: number;
+			/// How often to check a failing monitor in seconds until its incident is resolved, 0 disables it
+			failing_interval?: number;
+			/// How many probe locations must see a monitor as down to open an incident, 0 for a majority
+			location_quorum?: number;
+			/// How many times a monitor must go up or down within `flapping_window` to be flapping, 0 disables it
+			flapping_threshold?: number;
+			/// How far back in seconds to count the state changes of a monitor
+			flapping_window?: number;
+			/// When a location sees a monitor as down, `consecutive`, `k/n` failures of the last checks or `x%/tm` failures over the last minutes
+			failure_policy?: string;
 		}
 	},
+	maintenance?: {
+		/// Don't check monitors at all while an event linked to them is in progress
+		skip_checks: boolean;
+	},
+	proxy?: {
+		enabled: boolean;
+		url: string;
+		username?: string;
+		password?: string;
+		no_proxy: string[];
+	},
+	scheduler?: {
+		/// Spread monitors on the same interval across it instead of firing them all at once
+		stagger: boolean;
+		/// The maximum random delay in seconds added to each check
+		jitter: number;
+		/// The maximum number of checks running at once, 0 for no limit
+		max_concurrent: number;
+		/// The maximum number of checks running at once against the same host, 0 for no limit
+		max_per_host: number;
+		/// How often in seconds to compare the jobs with the database and fix any difference, 0 disables it
+		reconcile_interval: number;
+		/// How long in seconds to wait for running checks and notifications when shutting down
+		shutdown_timeout: number;
+	},
 	storage: {
 		max_size: number;
 	},
@@ -1,5 +1,7 @@
 import { Monitor } from "./monitor";
 
+export type EventStatus = "scheduled" | "in_progress" | "completed" | "cancelled";
+
 export interface Event {
 	id: string;
 	title: string;
@@ -8,7 +10,9 @@ export interface Event {
 	startsAt: Date;
 	duration: number;
 	autoComplete: boolean;
+	recurrence: string | null;
 	completed: boolean;
+	status: EventStatus;
 }
 
 export type EventWithMonitors = Event & {
@@ -8,6 +8,18 @@ export type Monitor = {
 	type: "http" | "tcp";
 	url: string;
 	interval: number;
+	intervalUnit: "seconds" | "minutes";
+	cron: string | null;
+	retries: number;
+	retryDelay: number;
+	paused: boolean;
+	proxy: string | null;
+	addressFamily: "auto" | "ipv4" | "ipv6" | "dual";
+	resolve: string | null;
+	dnsServer: string | null;
+	contentCheck: boolean;
+	contentIgnore: string[];
+	failurePolicy: string | null;
 	createdAt: Date;
 	updatedAt: Date;
 }
@@ -7,5 +7,33 @@ export type Ping = {
 	latency: number;
 	headers: unknown;
 	body: string | null;
+	addressFamily: "ipv4" | "ipv6" | null;
+	ip: string | null;
+	dnsLatency: number | null;
+	connectLatency: number | null;
+	tlsLatency: number | null;
+	ttfbLatency: number | null;
+	downloadLatency: number | null;
+	contentHash: string | null;
+	retries: PingAttempt[] | null;
+	queueLatency: number | null;
+	location: string | null;
+	maintenance: boolean;
 	createdAt: Date;
-}
\ No newline at end of file
+}
+
+export type PingAttempt = {
+	status: number | null;
+	latency: number;
+	error: string | null;
+	createdAt: string;
+}
+/// A monitor only some probe locations see as down, too few to open an incident
+export type Degradation = {
+	id: string;
+	monitorId: string;
+	addressFamily: "ipv4" | "ipv6" | null;
+	locations: string[];
+	startedAt: Date;
+	resolvedAt: Date | null;
+}